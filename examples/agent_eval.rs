@@ -0,0 +1,39 @@
+//! Evaluates the built-in tracking agent against an opponent that never moves, over a number of
+//! seeded episodes. Runs without SDL2:
+//!
+//! ```cargo run --release --no-default-features --example agent_eval```
+
+use rust_pong::agent::agents::{Agent, TrackingAgent};
+use rust_pong::PongEnv;
+
+/// The number of episodes to evaluate the agent on.
+const NUMBER_OF_EPISODES: u64 = 100;
+
+fn main() {
+    let mut env = PongEnv::new();
+    let mut agent = TrackingAgent;
+    let mut ops = Vec::new();
+
+    let mut total_reward = 0.0;
+    let mut wins = 0;
+
+    for episode in 0..NUMBER_OF_EPISODES {
+        env.reset(Some(episode));
+        loop {
+            agent.act(env.get_game(), env.get_agent_side(), &mut ops);
+            let (_, reward, done, info) = env.step(&mut ops);
+            total_reward += reward;
+            if done {
+                if info.winner == Some(env.get_agent_side()) {
+                    wins += 1;
+                }
+                break;
+            }
+        }
+    }
+
+    println!(
+        "won {wins} of {NUMBER_OF_EPISODES} episodes, mean reward {:.3}",
+        total_reward / NUMBER_OF_EPISODES as f32
+    );
+}
@@ -0,0 +1,60 @@
+//! Simulates games without a window, with every paddle moving randomly, and reports how fast the
+//! simulation runs. Runs without SDL2:
+//!
+//! ```cargo run --release --no-default-features --example headless_sim```
+
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rust_pong::{Operation, OperationTypes, PongEnv, Sides};
+
+/// The number of episodes to simulate.
+const NUMBER_OF_EPISODES: u64 = 1000;
+
+fn main() {
+    let mut env = PongEnv::new();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut ops = Vec::new();
+
+    let mut total_steps = 0;
+    let mut left_wins = 0;
+    let start = Instant::now();
+
+    for episode in 0..NUMBER_OF_EPISODES {
+        env.reset(Some(episode));
+        loop {
+            for side in [Sides::Left, Sides::Right] {
+                let paddle_count = match side {
+                    Sides::Left => env.get_game().get_left_paddle_count(),
+                    Sides::Right => env.get_game().get_right_paddle_count(),
+                };
+                for index in 0..paddle_count {
+                    let op_type = match rng.gen_range(0..3) {
+                        0 => OperationTypes::Up,
+                        1 => OperationTypes::Down,
+                        _ => OperationTypes::Stay,
+                    };
+                    ops.push(Operation::new(op_type, side, index));
+                }
+            }
+
+            let (_, _, done, info) = env.step(&mut ops);
+            if done {
+                total_steps += info.step_count;
+                if info.winner == Some(Sides::Left) {
+                    left_wins += 1;
+                }
+                break;
+            }
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{NUMBER_OF_EPISODES} episodes, {total_steps} steps in {elapsed:.2}s ({:.0} steps/s)",
+        total_steps as f64 / elapsed
+    );
+    println!("left side won {left_wins} of them");
+}
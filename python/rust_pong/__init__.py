@@ -0,0 +1,6 @@
+"""Python bindings of rust-pong, a pong environment for reinforcement learning."""
+
+from ._rust_pong import ACTION_DOWN, ACTION_STAY, ACTION_UP, RawPongEnv
+from .env import PongEnv
+
+__all__ = ["ACTION_DOWN", "ACTION_STAY", "ACTION_UP", "PongEnv", "RawPongEnv"]
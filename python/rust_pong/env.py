@@ -0,0 +1,110 @@
+"""The Gymnasium environment wrapping the Rust environment."""
+
+from typing import Any, Callable, Dict, Optional, Sequence
+
+import gymnasium as gym
+import numpy as np
+from gymnasium import spaces
+
+from ._rust_pong import ACTION_STAY, RawPongEnv
+
+#: A policy for the paddles of the other side. It takes the observation vector and returns one
+#: action code per paddle of its side.
+Opponent = Callable[[np.ndarray], Sequence[int]]
+
+
+class PongEnv(gym.Env):
+    """A Gymnasium environment of a default pong game.
+
+    The agent controls every paddle of its side, so an action holds one action code per paddle:
+    0 to stay, 1 to move up and 2 to move down. The paddles of the other side are controlled by
+    ``opponent``, and stay still if there is none. An episode is a single round.
+
+    With ``obs_type="vector"``, observations are the flat vectors described by
+    ``observation_names()``. With ``obs_type="pixels"``, they are frames of shape
+    ``(height, width, channels)``.
+    """
+
+    metadata = {"render_modes": ["rgb_array"], "render_fps": 50}
+
+    def __init__(
+        self,
+        agent_side: str = "left",
+        opponent: Optional[Opponent] = None,
+        obs_type: str = "vector",
+        downsample: int = 1,
+        grayscale: bool = False,
+        rewards: Optional[Dict[str, float]] = None,
+        render_mode: Optional[str] = None,
+    ):
+        if obs_type not in ("vector", "pixels"):
+            raise ValueError(f"unknown observation type {obs_type!r}")
+        if render_mode is not None and render_mode not in self.metadata["render_modes"]:
+            raise ValueError(f"unknown render mode {render_mode!r}")
+
+        self._raw = RawPongEnv(agent_side)
+        self._agent_side = agent_side
+        self._opponent_side = "right" if agent_side == "left" else "left"
+        self._opponent = opponent
+        self._obs_type = obs_type
+        self._downsample = downsample
+        self._grayscale = grayscale
+        self._last_vector: Optional[np.ndarray] = None
+        self.render_mode = render_mode
+
+        for name, value in (rewards or {}).items():
+            self._raw.add_reward(name, value)
+
+        self._agent_paddles = self._raw.paddle_count(agent_side)
+        self._opponent_paddles = self._raw.paddle_count(self._opponent_side)
+        self.action_space = spaces.MultiDiscrete([3] * self._agent_paddles)
+
+        if obs_type == "vector":
+            self.observation_space = spaces.Box(
+                low=-np.inf,
+                high=np.inf,
+                shape=(self._raw.observation_len,),
+                dtype=np.float32,
+            )
+        else:
+            shape = self._raw.render_pixels(downsample, grayscale).shape
+            self.observation_space = spaces.Box(low=0, high=255, shape=shape, dtype=np.uint8)
+
+    def observation_names(self) -> Sequence[str]:
+        """The name of every value in a vector observation, in order."""
+        return self._raw.observation_names()
+
+    def _observe(self, vector: np.ndarray) -> np.ndarray:
+        self._last_vector = vector
+        if self._obs_type == "vector":
+            return vector
+        return self._raw.render_pixels(self._downsample, self._grayscale)
+
+    def reset(
+        self, *, seed: Optional[int] = None, options: Optional[Dict[str, Any]] = None
+    ):
+        super().reset(seed=seed)
+        # Every episode is seeded from the Gymnasium generator, so that seeding the first reset
+        # makes the whole sequence of episodes reproducible.
+        raw_seed = int(self.np_random.integers(0, 2**63))
+        return self._observe(self._raw.reset(raw_seed)), {}
+
+    def step(self, action):
+        agent_actions = [int(a) for a in np.asarray(action).reshape(-1)]
+        if self._opponent is None:
+            opponent_actions = [ACTION_STAY] * self._opponent_paddles
+        else:
+            opponent_actions = [int(a) for a in self._opponent(self._last_vector)]
+
+        if self._agent_side == "left":
+            left, right = agent_actions, opponent_actions
+        else:
+            left, right = opponent_actions, agent_actions
+
+        vector, reward, done, info = self._raw.step(left, right)
+        return self._observe(vector), float(reward), done, False, info
+
+    def render(self):
+        if self.render_mode == "rgb_array":
+            return self._raw.render_pixels(1, False)
+        return None
@@ -0,0 +1,227 @@
+//! Python bindings of the pong environment. The extension module only exposes a thin wrapper
+//! around `PongEnv`, and the Gymnasium environment is written in Python on top of it.
+
+use numpy::{IntoPyArray, PyArray1, PyArray3, PyArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use rust_pong::{
+    env::{
+        pong_env::PongEnv,
+        reward::{DistanceToBallReward, PaddleHitReward, SurvivalReward},
+    },
+    game_and_scene::{
+        operation::{Operation, OperationTypes},
+        scene::Sides,
+    },
+    render::pixel_renderer::{PixelFormat, PixelRenderer},
+};
+
+/// The action code of the stay operation.
+pub const ACTION_STAY: u8 = 0;
+/// The action code of the up operation.
+pub const ACTION_UP: u8 = 1;
+/// The action code of the down operation.
+pub const ACTION_DOWN: u8 = 2;
+
+/// What `RawPongEnv.step` returns to Python, that are the observation, the reward, whether the
+/// episode is over and a dict of extra information.
+type PyStepResult<'py> = (Bound<'py, PyArray1<f32>>, f32, bool, Bound<'py, PyDict>);
+
+/// Parses the name of a side, that is either "left" or "right".
+fn parse_side(side: &str) -> PyResult<Sides> {
+    match side {
+        "left" => Ok(Sides::Left),
+        "right" => Ok(Sides::Right),
+        _ => Err(PyValueError::new_err(format!(
+            "Error: unknown side {side:?}, expected \"left\" or \"right\"."
+        ))),
+    }
+}
+
+/// The name of a side, as used on the Python side.
+fn side_name(side: Sides) -> &'static str {
+    match side {
+        Sides::Left => "left",
+        Sides::Right => "right",
+    }
+}
+
+/// Converts the action codes of the paddles of one side into operations.
+fn parse_actions(
+    actions: &[u8],
+    side: Sides,
+    paddle_count: usize,
+    ops: &mut Vec<Operation>,
+) -> PyResult<()> {
+    if actions.len() > paddle_count {
+        return Err(PyValueError::new_err(format!(
+            "Error: got {} actions for the {} side, but it only has {} paddles.",
+            actions.len(),
+            side_name(side),
+            paddle_count
+        )));
+    }
+
+    for (index, &action) in actions.iter().enumerate() {
+        let op_type = match action {
+            ACTION_STAY => OperationTypes::Stay,
+            ACTION_UP => OperationTypes::Up,
+            ACTION_DOWN => OperationTypes::Down,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Error: unknown action {action}, expected 0 (stay), 1 (up) or 2 (down)."
+                )))
+            }
+        };
+        ops.push(Operation::new(op_type, side, index));
+    }
+    Ok(())
+}
+
+/// The Rust environment. It is not meant to be used directly: use `rust_pong.PongEnv`, which
+/// wraps it into a Gymnasium environment.
+#[pyclass(name = "RawPongEnv", unsendable)]
+struct RawPongEnv {
+    env: PongEnv,
+}
+
+#[pymethods]
+impl RawPongEnv {
+    #[new]
+    #[pyo3(signature = (agent_side = "left"))]
+    fn new(agent_side: &str) -> PyResult<Self> {
+        let mut env = PongEnv::new();
+        env.set_agent_side(parse_side(agent_side)?);
+        Ok(Self { env })
+    }
+
+    /// The number of values in an observation.
+    #[getter]
+    fn observation_len(&self) -> usize {
+        self.env.get_observation_layout().len()
+    }
+
+    /// The name of every value in an observation, in order.
+    fn observation_names(&self) -> Vec<String> {
+        self.env.get_observation_layout().names()
+    }
+
+    /// The number of paddles on the given side.
+    fn paddle_count(&self, side: &str) -> PyResult<usize> {
+        let layout = self.env.get_observation_layout();
+        Ok(match parse_side(side)? {
+            Sides::Left => layout.max_left_paddles,
+            Sides::Right => layout.max_right_paddles,
+        })
+    }
+
+    /// Adds one of the built-in reward functions to the environment, on top of the win/loss
+    /// reward. The name is one of "paddle_hit", "survival" and "distance_to_ball".
+    fn add_reward(&mut self, name: &str, value: f32) -> PyResult<()> {
+        match name {
+            "paddle_hit" => self
+                .env
+                .add_reward_fn(Box::new(PaddleHitReward { per_hit: value })),
+            "survival" => self
+                .env
+                .add_reward_fn(Box::new(SurvivalReward { per_step: value })),
+            "distance_to_ball" => self
+                .env
+                .add_reward_fn(Box::new(DistanceToBallReward { scale: value })),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Error: unknown reward function {name:?}."
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts a new episode and returns the first observation.
+    #[pyo3(signature = (seed = None))]
+    fn reset<'py>(&mut self, py: Python<'py>, seed: Option<u64>) -> Bound<'py, PyArray1<f32>> {
+        self.env.reset(seed).into_values().into_pyarray(py)
+    }
+
+    /// Advances the game by one frame. Each list holds one action code per paddle of its side,
+    /// and missing actions mean stay. Returns the observation, the reward, whether the episode is
+    /// over and a dict of extra information.
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        left_actions: Vec<u8>,
+        right_actions: Vec<u8>,
+    ) -> PyResult<PyStepResult<'py>> {
+        let game = self.env.get_game();
+        let mut ops = Vec::new();
+        parse_actions(
+            &left_actions,
+            Sides::Left,
+            game.get_left_paddle_count(),
+            &mut ops,
+        )?;
+        parse_actions(
+            &right_actions,
+            Sides::Right,
+            game.get_right_paddle_count(),
+            &mut ops,
+        )?;
+
+        let (observation, reward, done, info) = self.env.step(&mut ops);
+
+        let py_info = PyDict::new(py);
+        py_info.set_item("winner", info.winner.map(side_name))?;
+        let events: Vec<(&str, usize)> = info
+            .events
+            .iter()
+            .map(|event| (side_name(event.side), event.ball_index))
+            .collect();
+        py_info.set_item("events", events)?;
+        py_info.set_item("scores", info.scores)?;
+        py_info.set_item("step_count", info.step_count)?;
+        py_info.set_item("reward_components", info.reward_components)?;
+
+        Ok((
+            observation.into_values().into_pyarray(py),
+            reward,
+            done,
+            py_info,
+        ))
+    }
+
+    /// Renders the current frame into an array of shape (height, width, channels), where there
+    /// are 3 channels for RGB frames and 1 channel for grayscale frames.
+    #[pyo3(signature = (downsample = 1, grayscale = false))]
+    fn render_pixels<'py>(
+        &self,
+        py: Python<'py>,
+        downsample: u32,
+        grayscale: bool,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let format = if grayscale {
+            PixelFormat::Grayscale
+        } else {
+            PixelFormat::Rgb
+        };
+        let game = self.env.get_game();
+        let renderer = PixelRenderer::downsampled(game.get_scene().get_arena(), downsample, format);
+        let frame = renderer.render(game);
+        frame.into_pyarray(py).reshape([
+            renderer.get_height() as usize,
+            renderer.get_width() as usize,
+            format.bytes_per_pixel(),
+        ])
+    }
+}
+
+/// The extension module.
+#[pymodule]
+fn _rust_pong(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<RawPongEnv>()?;
+    m.add("ACTION_STAY", ACTION_STAY)?;
+    m.add("ACTION_UP", ACTION_UP)?;
+    m.add("ACTION_DOWN", ACTION_DOWN)?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+import numpy as np
+import pytest
+from gymnasium.utils.env_checker import check_env
+
+from rust_pong import ACTION_DOWN, ACTION_UP, PongEnv, RawPongEnv
+
+
+def test_raw_env_observation_matches_names():
+    raw = RawPongEnv()
+    obs = raw.reset(0)
+    assert obs.dtype == np.float32
+    assert obs.shape == (raw.observation_len,)
+    assert len(raw.observation_names()) == raw.observation_len
+
+
+def test_raw_env_rejects_bad_actions():
+    raw = RawPongEnv()
+    raw.reset(0)
+    with pytest.raises(ValueError):
+        raw.step([7], [])
+    with pytest.raises(ValueError):
+        raw.step([0, 0, 0], [])
+
+
+def test_same_seed_gives_same_trajectory():
+    def rollout(seed):
+        env = PongEnv()
+        obs, _ = env.reset(seed=seed)
+        trajectory = [obs]
+        for i in range(200):
+            action = [ACTION_UP if i % 2 else ACTION_DOWN] * 2
+            obs, _, terminated, _, _ = env.step(action)
+            trajectory.append(obs)
+            if terminated:
+                break
+        return np.stack(trajectory)
+
+    np.testing.assert_array_equal(rollout(7), rollout(7))
+
+
+def test_episode_ends_with_sparse_reward():
+    # in the default scoring mode, the episode ends in the first step where a ball flies out, and
+    # every ball that flies out in that step is worth 1 for the side that scores
+    env = PongEnv()
+    env.reset(seed=1)
+    env.action_space.seed(1)
+    terminated, reward, info = False, 0.0, {}
+    while not terminated:
+        _, reward, terminated, _, info = env.step(env.action_space.sample())
+        if not terminated:
+            assert reward == 0.0
+            assert info["events"] == []
+
+    sides = [side for side, _ in info["events"]]
+    assert sides
+    assert reward == sides.count("left") - sides.count("right")
+    if sides.count("left") > sides.count("right"):
+        assert info["winner"] == "left"
+    elif sides.count("left") < sides.count("right"):
+        assert info["winner"] == "right"
+    else:
+        assert info["winner"] is None
+
+
+def test_reward_components_are_reported():
+    env = PongEnv(rewards={"survival": 0.01})
+    env.reset(seed=2)
+    _, reward, _, _, info = env.step([0, 0])
+    assert set(info["reward_components"]) == {"win_loss", "survival"}
+    assert reward == pytest.approx(sum(info["reward_components"].values()))
+
+
+def test_pixel_observations():
+    env = PongEnv(obs_type="pixels", downsample=4, grayscale=True)
+    obs, _ = env.reset(seed=3)
+    assert obs.dtype == np.uint8
+    assert obs.shape == env.observation_space.shape
+    assert obs.shape[2] == 1
+    assert obs.max() == 255
+
+
+def test_passes_gymnasium_env_checker():
+    check_env(PongEnv(), skip_render_check=True)
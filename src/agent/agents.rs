@@ -0,0 +1,63 @@
+use crate::game_and_scene::{
+    game::Game,
+    operation::{Operation, OperationTypes},
+    scene::Sides,
+};
+
+/// Who controls the paddles of a side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// The paddles are controlled by a human with the keyboard.
+    Human,
+    /// The paddles are controlled by the built-in AI, that is `TrackingAgent`.
+    Ai,
+    /// Nobody controls the paddles, so they never move.
+    Idle,
+}
+
+/// An agent controls all paddles of one side of a game. It is asked for its operations once per
+/// frame, before the game is updated.
+pub trait Agent {
+    /// Pushes the operations of this frame for the paddles of the given side.
+    fn act(&mut self, game: &Game, side: Sides, ops: &mut Vec<Operation>);
+}
+
+/// An agent that never moves its paddles.
+#[derive(Debug, Clone, Default)]
+pub struct IdleAgent;
+
+impl Agent for IdleAgent {
+    fn act(&mut self, _game: &Game, _side: Sides, _ops: &mut Vec<Operation>) {}
+}
+
+/// An agent that moves each of its paddles towards the ball closest to it horizontally.
+#[derive(Debug, Clone, Default)]
+pub struct TrackingAgent;
+
+impl Agent for TrackingAgent {
+    fn act(&mut self, game: &Game, side: Sides, ops: &mut Vec<Operation>) {
+        let paddles = match side {
+            Sides::Left => game.get_left_paddles(),
+            Sides::Right => game.get_right_paddles(),
+        };
+
+        for (index, paddle) in paddles.iter().enumerate() {
+            let paddle_pos = paddle.get_pos();
+            let closest_ball = game.get_balls().iter().min_by(|a, b| {
+                let distance_a = (a.get_pos().x - paddle_pos.x).abs();
+                let distance_b = (b.get_pos().x - paddle_pos.x).abs();
+                distance_a.total_cmp(&distance_b)
+            });
+
+            // the paddle only moves if the ball is far enough from its center, otherwise it
+            // would keep overshooting the ball and moving back and forth
+            let dead_zone = (paddle.get_height() / 4) as f32;
+            let op_type = match closest_ball {
+                Some(ball) if ball.get_pos().y < paddle_pos.y - dead_zone => OperationTypes::Up,
+                Some(ball) if ball.get_pos().y > paddle_pos.y + dead_zone => OperationTypes::Down,
+                _ => OperationTypes::Stay,
+            };
+            ops.push(Operation::new(op_type, side, index));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::error::Error;
+
+use crate::game_and_scene::{
+    game::Game, game_mode::GameMode, replay::Replay, scene::Sides, scene_config::ConfigError,
+};
+
+use super::agents::Agent;
+
+/// The outcome of running a number of games.
+#[derive(Debug, Clone, Default)]
+pub struct GamesSummary {
+    /// The number of games won by the left side.
+    pub left_wins: u32,
+    /// The number of games won by the right side.
+    pub right_wins: u32,
+    /// The number of games stopped because they reached the maximum number of frames, and of
+    /// games where both sides scored as many points.
+    pub draws: u32,
+    /// The total number of frames over all games.
+    pub frames: u64,
+}
+
+impl GamesSummary {
+    /// Counts a game won by the given side, or a draw if there is no winner.
+    fn add_result(&mut self, winner: Option<Sides>) {
+        match winner {
+            Some(Sides::Left) => self.left_wins += 1,
+            Some(Sides::Right) => self.right_wins += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// Runs the given number of games of the given game mode one after another on the same game,
+/// without a window. Each game is a single round, won by the side that scored the most points
+/// during the round, and the rounds are played as the rounds of matches, so a new match starts
+/// whenever one is over. Each frame, both agents are asked for their operations, and then the game
+/// is updated. A game that reaches `max_frames` frames without a winner is stopped and counted as
+/// a draw, and the match goes on with its scores unchanged. If a replay is given, the operations
+/// of every frame are recorded into it.
+pub fn run_games(
+    game: &mut Game,
+    game_mode: &GameMode,
+    number_of_games: u32,
+    max_frames: Option<u64>,
+    left: &mut dyn Agent,
+    right: &mut dyn Agent,
+    mut replay: Option<&mut Replay>,
+) -> Result<GamesSummary, ConfigError> {
+    let mut summary = GamesSummary::default();
+    let mut ops = Vec::new();
+
+    for _ in 0..number_of_games {
+        game.start_next_round(game_mode)?;
+        if let Some(replay) = replay.as_deref_mut() {
+            replay.start_round();
+        }
+
+        let mut frames = 0;
+        loop {
+            if max_frames.is_some_and(|max_frames| frames >= max_frames) {
+                game.end_round();
+                summary.draws += 1;
+                break;
+            }
+
+            left.act(game, Sides::Left, &mut ops);
+            right.act(game, Sides::Right, &mut ops);
+            if let Some(replay) = replay.as_deref_mut() {
+                replay.push_frame(&ops);
+            }
+
+            frames += 1;
+            game.update(&mut ops);
+            if game.is_round_over() {
+                summary.add_result(game.get_round_winner());
+                break;
+            }
+        }
+        summary.frames += frames;
+    }
+
+    Ok(summary)
+}
+
+/// Plays a replay back on the given game without a window. The game should be constructed with
+/// the seed of the replay and the game mode should be the one the replay was recorded with,
+/// otherwise the games will play out differently. A round whose frames run out before it has a
+/// winner is counted as a draw. Returns an error if the replay moves paddles that do not exist in
+/// the game mode.
+pub fn run_replay(
+    game: &mut Game,
+    game_mode: &GameMode,
+    replay: &Replay,
+) -> Result<GamesSummary, Box<dyn Error>> {
+    replay.validate(game_mode)?;
+
+    let mut summary = GamesSummary::default();
+    let mut ops = Vec::new();
+
+    for round in replay.get_rounds().iter() {
+        game.start_next_round(game_mode)?;
+
+        for frame in round.iter() {
+            ops.extend_from_slice(frame);
+            summary.frames += 1;
+            game.update(&mut ops);
+            if game.is_round_over() {
+                break;
+            }
+        }
+
+        if game.is_round_over() {
+            summary.add_result(game.get_round_winner());
+        } else {
+            game.end_round();
+            summary.draws += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::agents::IdleAgent;
+    use crate::game_and_scene::game::GameState;
+
+    #[test]
+    fn draws_keep_the_scores_of_the_match() {
+        let mut game = Game::with_seed(1);
+        let (mut left, mut right) = (IdleAgent, IdleAgent);
+
+        // nobody moves, so the first round ends when a ball flies out
+        let summary = run_games(
+            &mut game,
+            &GameMode::Default,
+            1,
+            None,
+            &mut left,
+            &mut right,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.draws, 0);
+        let scores = game.get_scores();
+        assert_ne!(scores, (0, 0));
+
+        let summary = run_games(
+            &mut game,
+            &GameMode::Default,
+            2,
+            Some(1),
+            &mut left,
+            &mut right,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.draws, 2);
+        assert_eq!(game.get_scores(), scores);
+        assert_eq!(game.get_round(), 3);
+        assert_eq!(game.get_state(), GameState::RoundOver);
+    }
+}
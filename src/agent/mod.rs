@@ -0,0 +1,5 @@
+/// The Agent trait, the Controller enum and the built-in agents.
+pub mod agents;
+
+/// Running whole games between agents without a window.
+pub mod headless;
@@ -0,0 +1,133 @@
+use sdl2::keyboard::Keycode;
+
+use crate::game_and_scene::{operation::Operation, scene::Sides};
+
+/// The keys that control paddles and are currently held down, in the order they were pressed.
+/// Paddles move every frame while their key is held, instead of only on the key-repeat events of
+/// the operating system.
+///
+/// When both keys of a paddle are held, the one pressed last wins, and the paddle goes back to the
+/// other direction when it is released.
+#[derive(Debug, Clone, Default)]
+pub struct HeldKeys {
+    /// The held keys that map to an operation, the most recently pressed last.
+    keys: Vec<Keycode>,
+}
+
+impl HeldKeys {
+    /// Constructs an empty set of held keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that a key was pressed. Keys that do not control a paddle are ignored.
+    pub fn key_down(&mut self, keycode: Keycode) {
+        if Operation::from_key_code(keycode).is_some() && !self.keys.contains(&keycode) {
+            self.keys.push(keycode);
+        }
+    }
+
+    /// Records that a key was released.
+    pub fn key_up(&mut self, keycode: Keycode) {
+        self.keys.retain(|&key| key != keycode);
+    }
+
+    /// Releases every key, for example when the window loses the focus.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Pushes one operation for each paddle that has a held key and whose side is accepted by the
+    /// filter. If both keys of a paddle are held, the one pressed last wins. `paddle_counts` are
+    /// the numbers of left and right paddles in the game, and the keys of the paddles beyond them
+    /// are ignored, since a custom game may have fewer paddles than there are keys.
+    pub fn push_operations(
+        &self,
+        accept_side: impl Fn(Sides) -> bool,
+        paddle_counts: (usize, usize),
+        ops: &mut Vec<Operation>,
+    ) {
+        let mut pushed: Vec<(Sides, usize)> = Vec::new();
+        for &key in self.keys.iter().rev() {
+            let Some(op) = Operation::from_key_code(key) else {
+                continue;
+            };
+            let paddle_count = match op.side {
+                Sides::Left => paddle_counts.0,
+                Sides::Right => paddle_counts.1,
+            };
+            if accept_side(op.side)
+                && op.index < paddle_count
+                && !pushed.contains(&(op.side, op.index))
+            {
+                pushed.push((op.side, op.index));
+                ops.push(op);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_and_scene::operation::OperationTypes;
+
+    /// The operations pushed for the held keys of a default game with both sides accepted, as
+    /// their side, the index of their paddle and whether they move up.
+    fn pushed(held_keys: &HeldKeys) -> Vec<(Sides, usize, bool)> {
+        let mut ops = Vec::new();
+        held_keys.push_operations(|_| true, (2, 2), &mut ops);
+        ops.iter()
+            .map(|op| (op.side, op.index, matches!(op.op_type, OperationTypes::Up)))
+            .collect()
+    }
+
+    #[test]
+    fn last_pressed_of_two_opposing_keys_wins() {
+        let mut held_keys = HeldKeys::new();
+
+        held_keys.key_down(Keycode::W);
+        held_keys.key_down(Keycode::S);
+        assert_eq!(pushed(&held_keys), vec![(Sides::Left, 0, false)]);
+
+        // releasing the last pressed key goes back to the other direction
+        held_keys.key_up(Keycode::S);
+        assert_eq!(pushed(&held_keys), vec![(Sides::Left, 0, true)]);
+
+        held_keys.key_up(Keycode::W);
+        assert!(pushed(&held_keys).is_empty());
+    }
+
+    #[test]
+    fn releasing_one_of_two_keys_keeps_the_other_paddle_moving() {
+        let mut held_keys = HeldKeys::new();
+
+        held_keys.key_down(Keycode::W);
+        held_keys.key_down(Keycode::Down);
+        held_keys.key_down(Keycode::Q);
+        assert_eq!(
+            pushed(&held_keys),
+            vec![(Sides::Right, 0, false), (Sides::Left, 0, true)]
+        );
+
+        held_keys.key_up(Keycode::W);
+        assert_eq!(pushed(&held_keys), vec![(Sides::Right, 0, false)]);
+
+        held_keys.clear();
+        assert!(pushed(&held_keys).is_empty());
+    }
+
+    #[test]
+    fn keys_of_other_sides_and_missing_paddles_are_ignored() {
+        let mut held_keys = HeldKeys::new();
+        held_keys.key_down(Keycode::A);
+        held_keys.key_down(Keycode::W);
+        held_keys.key_down(Keycode::Up);
+
+        let mut ops = Vec::new();
+        held_keys.push_operations(|side| side == Sides::Left, (1, 2), &mut ops);
+
+        assert_eq!(ops.len(), 1);
+        assert_eq!((ops[0].side, ops[0].index), (Sides::Left, 0));
+    }
+}
@@ -0,0 +1,5 @@
+/// The HeldKeys struct, turning held keys into operations.
+pub mod input;
+
+/// The PlayConfig struct and the play function.
+pub mod play;
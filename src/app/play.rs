@@ -0,0 +1,490 @@
+use std::error::Error;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::EventPump;
+
+use super::input::HeldKeys;
+use crate::{
+    agent::agents::{Agent, Controller, TrackingAgent},
+    game_and_scene::{
+        arena::{DEFAULT_ARENA_HEIGHT, DEFAULT_ARENA_WIDTH},
+        game::{Game, GameState},
+        game_mode::GameMode,
+        operation::Operation,
+        replay::Replay,
+        scene::Sides,
+        timestep::FixedTimestep,
+    },
+    render::game_renderer::{GameRenderer, DEFAULT_BACKGROUND_COLOR},
+};
+
+/// The time interval between each rendered frame, that is 60 frames per second. The simulation
+/// runs at its own fixed rate of `UPDATES_PER_SECOND` whatever the frame rate is, and the frames
+/// in between two updates are interpolated.
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The default number of games played without a window, where each game is a single round.
+pub const DEFAULT_NUMBER_OF_GAMES: u32 = 10;
+
+/// How long the balls wait in the center of the arena at the start of each round before they are
+/// launched, so that the players can see where they start.
+pub const SERVE_DELAY: Duration = Duration::from_secs(1);
+
+/// How long the end of a round is shown before the next round starts, unless a key is pressed.
+pub const ROUND_OVER_DELAY: Duration = Duration::from_secs(2);
+
+/// How long the end of a match and its winner are shown before the next match starts or the
+/// window closes, unless a key is pressed.
+pub const MATCH_OVER_DELAY: Duration = Duration::from_secs(5);
+
+/// The default number of matches played in a window. A match is played until a side reaches the
+/// target score, so the program terminates after one match by default.
+pub const DEFAULT_NUMBER_OF_MATCHES: u32 = 1;
+
+/// Everything that can be changed about a game played in a window.
+pub struct PlayConfig {
+    /// The time interval between each rendered frame. It does not change the speed of the game,
+    /// which is updated `UPDATES_PER_SECOND` times per second of real time.
+    pub frame_duration: Duration,
+    /// Whether frames are rendered as fast as possible. If true, `frame_duration` will be ignored
+    /// and each frame is rendered as soon as the previous one is presented. The game still runs
+    /// at the same speed.
+    pub full_speed: bool,
+    /// Whether it is a customized game or it is a default game. Keyboard inputs can only control
+    /// the first 2 paddles of each side, so the other paddles of a custom game should be
+    /// controlled by the AI.
+    pub game_mode: GameMode,
+    /// Who controls the left paddles. Keyboard inputs are only read for the sides controlled by
+    /// humans.
+    pub left: Controller,
+    /// Who controls the right paddles.
+    pub right: Controller,
+    /// The number of matches. The program will terminate after this number of matches. The target
+    /// score of a match is given by the scoring of the game mode.
+    pub number_of_matches: u32,
+    /// The seed of the game. If there is none, a random seed is used.
+    pub seed: Option<u64>,
+    /// The width of the window. The arena of the game mode is scaled to fit into the window.
+    pub window_width: u32,
+    /// The height of the window.
+    pub window_height: u32,
+    /// Whether the escape key asks for a confirmation before quitting. Closing the window always
+    /// quits right away.
+    pub confirm_quit: bool,
+}
+
+impl Default for PlayConfig {
+    /// Generate the config of a default game played by humans.
+    fn default() -> Self {
+        Self {
+            frame_duration: FRAME_DURATION,
+            full_speed: false,
+            game_mode: GameMode::Default,
+            left: Controller::Human,
+            right: Controller::Human,
+            number_of_matches: DEFAULT_NUMBER_OF_MATCHES,
+            seed: None,
+            window_width: DEFAULT_ARENA_WIDTH,
+            window_height: DEFAULT_ARENA_HEIGHT,
+            confirm_quit: false,
+        }
+    }
+}
+
+/// Opens a window and returns a renderer of the given game drawing into it, together with the
+/// event pump of the window.
+fn open_window(
+    config: &PlayConfig,
+    game: Game,
+) -> Result<(GameRenderer, EventPump), Box<dyn Error>> {
+    // These codes are copied from sdl2 docs
+    // https://docs.rs/sdl2/latest/sdl2/
+    let sdl_context = sdl2::init()?;
+
+    let video_subsystem = sdl_context.video()?;
+
+    let window = video_subsystem
+        .window("Rust Pong", config.window_width, config.window_height)
+        .position_centered()
+        .build()?;
+
+    let mut canvas = window.into_canvas().build()?;
+    let arena = config.game_mode.get_arena();
+    canvas.set_logical_size(arena.width, arena.height)?;
+    let event_pump = sdl_context.event_pump()?;
+
+    canvas.set_draw_color(DEFAULT_BACKGROUND_COLOR);
+    canvas.clear();
+    canvas.present();
+
+    Ok((GameRenderer::new(game, canvas), event_pump))
+}
+
+/// The state of the loop of a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopState {
+    /// The game is updated at its fixed rate.
+    Playing,
+    /// The player pressed the pause key. The game is frozen until the key is pressed again.
+    Paused,
+    /// The player asked to quit and has to confirm it. The game is frozen meanwhile, and goes
+    /// back to being paused or played when the player does not confirm, depending on
+    /// `was_paused`.
+    ConfirmingQuit {
+        /// Whether the game was paused when the player asked to quit.
+        was_paused: bool,
+    },
+    /// The loop stops right away.
+    Quitting,
+}
+
+impl LoopState {
+    /// The state after the given event. Closing the window always quits right away, and the
+    /// escape key either quits right away or asks for a confirmation first.
+    fn next(self, event: &Event, confirm_quit: bool) -> Self {
+        use LoopState::*;
+
+        let key = match event {
+            Event::Quit { .. } => return Quitting,
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat: false,
+                ..
+            } => *key,
+            _ => return self,
+        };
+
+        match (self, key) {
+            (ConfirmingQuit { .. }, Keycode::Y | Keycode::Return) => Quitting,
+            (ConfirmingQuit { was_paused }, Keycode::N | Keycode::Escape) => {
+                if was_paused {
+                    Paused
+                } else {
+                    Playing
+                }
+            }
+            (ConfirmingQuit { .. }, _) => self,
+            (_, Keycode::Escape) if confirm_quit => ConfirmingQuit {
+                was_paused: self == Paused,
+            },
+            (_, Keycode::Escape) => Quitting,
+            (Playing, PAUSE_KEY) => Paused,
+            (Paused, PAUSE_KEY) => Playing,
+            (state, _) => state,
+        }
+    }
+
+    /// The lines of the overlay drawn on top of the game in this state.
+    fn overlay(self) -> &'static [&'static str] {
+        match self {
+            LoopState::ConfirmingQuit { .. } => &["QUIT? Y/N"],
+            _ => &[],
+        }
+    }
+}
+
+/// The key that pauses and resumes the game.
+pub const PAUSE_KEY: Keycode = Keycode::P;
+
+/// A countdown of real time, such as the serve delay. It only runs down by the time it is
+/// advanced by, so it stands still while the game is paused.
+#[derive(Debug, Clone, Copy)]
+struct Countdown {
+    /// The time left.
+    left: Duration,
+}
+
+impl Countdown {
+    /// Starts a countdown of the given duration.
+    fn new(duration: Duration) -> Self {
+        Self { left: duration }
+    }
+
+    /// Counts the given time down. Returns whether the countdown is over.
+    fn advance(&mut self, elapsed: Duration) -> bool {
+        self.left = self.left.saturating_sub(elapsed);
+        self.left.is_zero()
+    }
+}
+
+/// How long the end of the current round or match of the game is shown.
+fn end_of_round_delay(game: &Game) -> Duration {
+    match game.get_state() {
+        GameState::MatchOver => MATCH_OVER_DELAY,
+        _ => ROUND_OVER_DELAY,
+    }
+}
+
+/// Opens a window and plays matches in it until the number of matches is reached or the player
+/// quits. Returns a replay of everything that was played, with one replay round per round.
+///
+/// The loop is driven by a `LoopState`. Closing the window or pressing escape stops it right
+/// away, unless `confirm_quit` is set, in which case escape asks for a confirmation first.
+/// `PAUSE_KEY` pauses and resumes the game, and nothing is recorded while the game is frozen.
+///
+/// Each round is served after `SERVE_DELAY`. The end of a round is shown for `ROUND_OVER_DELAY`
+/// and the end of a match for `MATCH_OVER_DELAY`, or until any other key is pressed, before the
+/// next round or match starts.
+///
+/// The game is updated at a fixed time step, independently of the frame rate. Every frame, the
+/// real time elapsed since the previous frame is handed to a `FixedTimestep`, which tells how many
+/// updates to run, and the frame is drawn in between the last two states of the game.
+pub fn play(config: &PlayConfig) -> Result<Replay, Box<dyn Error>> {
+    if let GameMode::Custom(scene_config) = &config.game_mode {
+        scene_config.validate()?;
+    }
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let (mut renderer, mut event_pump) = open_window(config, Game::with_seed(seed))?;
+    let mut replay = Replay::new(seed);
+
+    let mut agents: [(Sides, Controller, TrackingAgent); 2] = [
+        (Sides::Left, config.left, TrackingAgent),
+        (Sides::Right, config.right, TrackingAgent),
+    ];
+    let is_human = |side: Sides| match side {
+        Sides::Left => config.left == Controller::Human,
+        Sides::Right => config.right == Controller::Human,
+    };
+
+    let mut ops: Vec<Operation> = Vec::new();
+    let mut held_keys = HeldKeys::new();
+    let mut state = LoopState::Playing;
+    let mut matches_played = 0;
+    let mut timestep = FixedTimestep::default();
+    let mut countdown = Countdown::new(SERVE_DELAY);
+    let mut last_frame = Instant::now();
+
+    if config.number_of_matches == 0 {
+        return Ok(replay);
+    }
+    renderer.start_next_round(&config.game_mode)?;
+    replay.start_round();
+
+    loop {
+        let frame_start = Instant::now();
+        let elapsed = frame_start - last_frame;
+        last_frame = frame_start;
+
+        let mut key_pressed = false;
+        for event in event_pump.poll_iter() {
+            let previous_state = state;
+            state = state.next(&event, config.confirm_quit);
+            match event {
+                Event::KeyDown {
+                    keycode: Some(key),
+                    repeat: false,
+                    ..
+                } => {
+                    held_keys.key_down(key);
+                    // keys that pause or quit do not skip the end of a round
+                    key_pressed |= previous_state == LoopState::Playing && state == previous_state;
+                }
+                Event::KeyUp {
+                    keycode: Some(key), ..
+                } => held_keys.key_up(key),
+                Event::Window {
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } => held_keys.clear(),
+                _ => {}
+            }
+        }
+
+        match state {
+            LoopState::Quitting => break,
+            LoopState::Paused | LoopState::ConfirmingQuit { .. } => renderer.pause_game(),
+            LoopState::Playing => {
+                renderer.resume_game();
+
+                let game = renderer.get_game();
+                if game.get_state() == GameState::Serving && !countdown.advance(elapsed) {
+                    // the balls wait in the center until they are served
+                    timestep.reset();
+                } else if game.is_round_over() {
+                    if key_pressed || countdown.advance(elapsed) {
+                        if game.get_state() == GameState::MatchOver {
+                            matches_played += 1;
+                            if matches_played >= config.number_of_matches {
+                                break;
+                            }
+                        }
+                        renderer.start_next_round(&config.game_mode)?;
+                        replay.start_round();
+                        timestep.reset();
+                        countdown = Countdown::new(SERVE_DELAY);
+                    }
+                } else {
+                    for _ in 0..timestep.advance(elapsed) {
+                        let game = renderer.get_game();
+                        let paddle_counts =
+                            (game.get_left_paddle_count(), game.get_right_paddle_count());
+                        held_keys.push_operations(is_human, paddle_counts, &mut ops);
+                        for (side, controller, agent) in agents.iter_mut() {
+                            if let Controller::Ai = controller {
+                                agent.act(game, *side, &mut ops);
+                            }
+                        }
+                        replay.push_frame(&ops);
+                        renderer.update_game(&mut ops);
+                        if renderer.get_game().is_round_over() {
+                            countdown = Countdown::new(end_of_round_delay(renderer.get_game()));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        renderer.set_interpolation(timestep.alpha());
+        renderer.render_to_canvas_with_overlay(state.overlay())?;
+
+        if !config.full_speed {
+            sleep(config.frame_duration.saturating_sub(frame_start.elapsed()));
+        }
+    }
+
+    Ok(replay)
+}
+
+/// Opens a window and plays a replay back in it, until the replay ends or the player quits. Only
+/// the game mode, the frame rate, the window settings and `confirm_quit` of the config are used.
+/// The playback can be paused with `PAUSE_KEY`, and rounds are served and ended as in `play`. The
+/// game mode has to be the one the replay was recorded with, since replays do not store it. The
+/// replay runs at the speed it was played at, since each of its frames is a single update of the
+/// game. Returns an error if the replay moves paddles that do not exist in the game mode.
+pub fn replay(config: &PlayConfig, replay: &Replay) -> Result<(), Box<dyn Error>> {
+    replay.validate(&config.game_mode)?;
+
+    let (mut renderer, mut event_pump) = open_window(config, Game::with_seed(replay.get_seed()))?;
+    let mut ops: Vec<Operation> = Vec::new();
+    let mut state = LoopState::Playing;
+    let mut timestep = FixedTimestep::default();
+
+    for round in replay.get_rounds().iter() {
+        renderer.start_next_round(&config.game_mode)?;
+        timestep.reset();
+        let mut countdown = Countdown::new(SERVE_DELAY);
+
+        let mut frames = round.iter();
+        let mut last_frame = Instant::now();
+        loop {
+            let frame_start = Instant::now();
+            let elapsed = frame_start - last_frame;
+            last_frame = frame_start;
+
+            let mut key_pressed = false;
+            for event in event_pump.poll_iter() {
+                let previous_state = state;
+                state = state.next(&event, config.confirm_quit);
+                if let Event::KeyDown { repeat: false, .. } = event {
+                    key_pressed |= previous_state == LoopState::Playing && state == previous_state;
+                }
+            }
+
+            match state {
+                LoopState::Quitting => return Ok(()),
+                LoopState::Paused | LoopState::ConfirmingQuit { .. } => renderer.pause_game(),
+                LoopState::Playing => {
+                    renderer.resume_game();
+
+                    let game = renderer.get_game();
+                    if game.get_state() == GameState::Serving && !countdown.advance(elapsed) {
+                        timestep.reset();
+                    } else if game.is_round_over() {
+                        if key_pressed || countdown.advance(elapsed) {
+                            break;
+                        }
+                    } else {
+                        for _ in 0..timestep.advance(elapsed) {
+                            let Some(frame) = frames.next() else {
+                                // the recording stopped before the round was over, which counts
+                                // as a draw and keeps the scores of the match
+                                renderer.end_round();
+                                countdown = Countdown::new(ROUND_OVER_DELAY);
+                                break;
+                            };
+                            ops.extend_from_slice(frame);
+                            renderer.update_game(&mut ops);
+                            if renderer.get_game().is_round_over() {
+                                countdown = Countdown::new(end_of_round_delay(renderer.get_game()));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            renderer.set_interpolation(timestep.alpha());
+            renderer.render_to_canvas_with_overlay(state.overlay())?;
+            if !config.full_speed {
+                sleep(config.frame_duration.saturating_sub(frame_start.elapsed()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Mod;
+
+    use super::*;
+
+    /// The event of the given key being pressed.
+    fn key_down(key: Keycode) -> Event {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(key),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+
+    /// The state after pressing the given keys one after the other, starting from `state`.
+    fn press(state: LoopState, keys: &[Keycode], confirm_quit: bool) -> LoopState {
+        keys.iter().fold(state, |state, &key| {
+            state.next(&key_down(key), confirm_quit)
+        })
+    }
+
+    #[test]
+    fn declining_to_quit_goes_back_to_the_previous_state() {
+        use LoopState::*;
+
+        assert_eq!(
+            press(Paused, &[Keycode::Escape], true),
+            ConfirmingQuit { was_paused: true }
+        );
+        assert_eq!(press(Paused, &[Keycode::Escape, Keycode::N], true), Paused);
+        assert_eq!(
+            press(Paused, &[Keycode::Escape, Keycode::Escape], true),
+            Paused
+        );
+        assert_eq!(
+            press(Playing, &[Keycode::Escape, Keycode::N], true),
+            Playing
+        );
+        assert_eq!(
+            press(Paused, &[Keycode::Escape, Keycode::Y], true),
+            Quitting
+        );
+        assert_eq!(press(Paused, &[Keycode::Escape], false), Quitting);
+    }
+
+    #[test]
+    fn countdowns_only_run_down_when_advanced() {
+        let mut countdown = Countdown::new(Duration::from_millis(50));
+
+        assert!(!countdown.advance(Duration::from_millis(20)));
+        assert!(!countdown.advance(Duration::ZERO));
+        assert!(countdown.advance(Duration::from_millis(40)));
+        assert!(countdown.advance(Duration::ZERO));
+    }
+}
@@ -0,0 +1,11 @@
+/// The PongEnv struct.
+pub mod pong_env;
+
+/// The VecPongEnv struct.
+pub mod vec_env;
+
+/// The RewardFn trait and the built-in reward functions.
+pub mod reward;
+
+/// The Observation and ObservationLayout structs.
+pub mod observation;
@@ -0,0 +1,280 @@
+use crate::game_and_scene::game::Game;
+use crate::game_and_scene::scene::Scene;
+
+/// The number of values describing a paddle, that are its x and y positions and its mask.
+pub const PADDLE_FEATURES: usize = 3;
+/// The number of values describing a ball, that are its x and y positions, its x and y velocities
+/// and its mask.
+pub const BALL_FEATURES: usize = 5;
+/// The number of values describing the scoreboard, that are the scores of both sides.
+pub const SCORE_FEATURES: usize = 2;
+
+/// The layout of an observation. It fixes how many paddles and balls an observation has room for,
+/// so that observations of scenes with different numbers of paddles and balls all have the same
+/// length. Missing paddles and balls are padded with zeros, and each paddle and ball has a mask
+/// value which is 1.0 if it exists and 0.0 if it is padding.
+///
+/// The values are laid out in the following order:
+/// - for each left paddle, `x`, `y` and `mask`;
+/// - for each right paddle, `x`, `y` and `mask`;
+/// - for each ball, `x`, `y`, `vx`, `vy` and `mask`;
+/// - the left score and the right score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObservationLayout {
+    /// The number of left paddles the observation has room for.
+    pub max_left_paddles: usize,
+    /// The number of right paddles the observation has room for.
+    pub max_right_paddles: usize,
+    /// The number of balls the observation has room for.
+    pub max_balls: usize,
+}
+
+impl ObservationLayout {
+    /// The constructor.
+    pub fn new(max_left_paddles: usize, max_right_paddles: usize, max_balls: usize) -> Self {
+        Self {
+            max_left_paddles,
+            max_right_paddles,
+            max_balls,
+        }
+    }
+
+    /// Generates a layout that has exactly enough room for the given scene.
+    pub fn for_scene(scene: &Scene) -> Self {
+        Self {
+            max_left_paddles: scene.get_left_paddles().len(),
+            max_right_paddles: scene.get_right_paddles().len(),
+            max_balls: scene.get_balls().len(),
+        }
+    }
+
+    /// The total number of values in an observation with this layout.
+    pub fn len(&self) -> usize {
+        (self.max_left_paddles + self.max_right_paddles) * PADDLE_FEATURES
+            + self.max_balls * BALL_FEATURES
+            + SCORE_FEATURES
+    }
+
+    /// Whether an observation with this layout is empty. It never is, since the scores are always
+    /// part of the observation.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The index of the first value of the left paddle with the given index.
+    pub fn left_paddle_offset(&self, index: usize) -> usize {
+        index * PADDLE_FEATURES
+    }
+
+    /// The index of the first value of the right paddle with the given index.
+    pub fn right_paddle_offset(&self, index: usize) -> usize {
+        (self.max_left_paddles + index) * PADDLE_FEATURES
+    }
+
+    /// The index of the first value of the ball with the given index.
+    pub fn ball_offset(&self, index: usize) -> usize {
+        (self.max_left_paddles + self.max_right_paddles) * PADDLE_FEATURES + index * BALL_FEATURES
+    }
+
+    /// The index of the left score. The right score comes right after it.
+    pub fn scores_offset(&self) -> usize {
+        self.ball_offset(self.max_balls)
+    }
+
+    /// The names of all values in an observation, in order. For example, the name of the y
+    /// position of the second right paddle is `right_paddle_1_y`.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::with_capacity(self.len());
+
+        for (side, count) in [
+            ("left", self.max_left_paddles),
+            ("right", self.max_right_paddles),
+        ] {
+            for i in 0..count {
+                for feature in ["x", "y", "mask"] {
+                    names.push(format!("{side}_paddle_{i}_{feature}"));
+                }
+            }
+        }
+
+        for i in 0..self.max_balls {
+            for feature in ["x", "y", "vx", "vy", "mask"] {
+                names.push(format!("ball_{i}_{feature}"));
+            }
+        }
+
+        names.push(String::from("left_score"));
+        names.push(String::from("right_score"));
+
+        names
+    }
+
+    /// The index of the value with the given name, if there is one.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names().iter().position(|n| n == name)
+    }
+}
+
+impl Default for ObservationLayout {
+    /// Generates a layout with room for the scene of a default game, that is 2 paddles on each
+    /// side and 2 balls.
+    fn default() -> Self {
+        Self::new(2, 2, 2)
+    }
+}
+
+/// What an agent gets to see after each step of the environment. It is a flat vector of values
+/// laid out as described by its `ObservationLayout`. Positions and velocities are normalised to
+/// the size of the screen, so x values are divided by the screen width and y values are divided by
+/// the screen height. Velocities are thus in screens per second. Scores are not normalised.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// The layout of the values.
+    layout: ObservationLayout,
+    /// The values of the observation.
+    values: Vec<f32>,
+}
+
+impl Observation {
+    /// Builds an observation of the scene with the given layout. Paddles and balls that do not
+    /// fit in the layout are left out.
+    pub fn from_scene(scene: &Scene, scores: (u32, u32), layout: ObservationLayout) -> Self {
+        let width = scene.get_arena().width_f32();
+        let height = scene.get_arena().height_f32();
+        let mut values = vec![0.0; layout.len()];
+
+        for (paddles, max_paddles, offset) in [
+            (
+                scene.get_left_paddles(),
+                layout.max_left_paddles,
+                layout.left_paddle_offset(0),
+            ),
+            (
+                scene.get_right_paddles(),
+                layout.max_right_paddles,
+                layout.right_paddle_offset(0),
+            ),
+        ] {
+            for (i, paddle) in paddles.iter().take(max_paddles).enumerate() {
+                let start = offset + i * PADDLE_FEATURES;
+                let pos = paddle.get_pos();
+                values[start..start + PADDLE_FEATURES].copy_from_slice(&[
+                    pos.x / width,
+                    pos.y / height,
+                    1.0,
+                ]);
+            }
+        }
+
+        for (i, ball) in scene.get_balls().iter().take(layout.max_balls).enumerate() {
+            let start = layout.ball_offset(i);
+            let pos = ball.get_pos();
+            let vel = ball.get_vel();
+            values[start..start + BALL_FEATURES].copy_from_slice(&[
+                pos.x / width,
+                pos.y / height,
+                vel.x / width,
+                vel.y / height,
+                1.0,
+            ]);
+        }
+
+        let start = layout.scores_offset();
+        values[start] = scores.0 as f32;
+        values[start + 1] = scores.1 as f32;
+
+        Self { layout, values }
+    }
+
+    /// Builds an observation of the scene of the game with the given layout.
+    pub fn from_game(game: &Game, layout: ObservationLayout) -> Self {
+        Self::from_scene(game.get_scene(), game.get_scores(), layout)
+    }
+
+    /// Get the layout of the observation.
+    pub fn get_layout(&self) -> &ObservationLayout {
+        &self.layout
+    }
+
+    /// Get all values of the observation.
+    pub fn get_values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Get the value with the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.layout.index_of(name).map(|i| self.values[i])
+    }
+
+    /// Consumes the observation and returns its values.
+    pub fn into_values(self) -> Vec<f32> {
+        self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_and_scene::{arena::ArenaConfig, ball::Ball, paddle::Paddle};
+    use crate::math_utils::vec2::Vec2;
+
+    #[test]
+    fn missing_paddles_and_balls_are_padded_and_masked() {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![Paddle::default_left_paddle(&arena)]);
+        scene.add_balls(vec![Ball::new(
+            Vec2::new(arena.width_f32() / 4.0, arena.height_f32() / 2.0),
+            Vec2::new(arena.width_f32(), -arena.height_f32()),
+            3,
+        )]);
+        let layout = ObservationLayout::default();
+
+        let observation = Observation::from_scene(&scene, (3, 5), layout);
+
+        assert_eq!(observation.get_values().len(), layout.len());
+        assert_eq!(observation.get_values().len(), layout.names().len());
+        assert_eq!(observation.get("left_paddle_0_mask"), Some(1.0));
+        for name in [
+            "left_paddle_1",
+            "right_paddle_0",
+            "right_paddle_1",
+            "ball_1",
+        ] {
+            assert_eq!(observation.get(&format!("{name}_x")), Some(0.0), "{name}");
+            assert_eq!(observation.get(&format!("{name}_y")), Some(0.0), "{name}");
+            assert_eq!(
+                observation.get(&format!("{name}_mask")),
+                Some(0.0),
+                "{name}"
+            );
+        }
+        assert_eq!(observation.get("ball_0_x"), Some(0.25));
+        assert_eq!(observation.get("ball_0_y"), Some(0.5));
+        assert_eq!(observation.get("ball_0_vx"), Some(1.0));
+        assert_eq!(observation.get("ball_0_vy"), Some(-1.0));
+        assert_eq!(observation.get("ball_0_mask"), Some(1.0));
+        assert_eq!(observation.get("left_score"), Some(3.0));
+        assert_eq!(observation.get("right_score"), Some(5.0));
+    }
+
+    #[test]
+    fn paddles_and_balls_beyond_the_layout_are_left_out() {
+        let scene = Scene::construct_default_scene_with_2_balls(&mut rand::thread_rng());
+        let layout = ObservationLayout::new(1, 2, 1);
+
+        let observation = Observation::from_scene(&scene, (0, 0), layout);
+
+        assert_eq!(
+            observation.get_values().len(),
+            3 * PADDLE_FEATURES + BALL_FEATURES + 2
+        );
+        assert_eq!(observation.get("left_paddle_1_mask"), None);
+        assert_eq!(observation.get("ball_1_mask"), None);
+        assert_eq!(observation.get("right_paddle_1_mask"), Some(1.0));
+        assert_eq!(
+            observation.get("ball_0_x"),
+            Some(scene.get_balls()[0].get_pos().x / scene.get_arena().width_f32())
+        );
+    }
+}
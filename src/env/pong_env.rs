@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use crate::game_and_scene::{
+    game::Game,
+    game_mode::GameMode,
+    operation::Operation,
+    scene::{ScoringEvent, Sides},
+    scene_config::ConfigError,
+};
+
+use super::observation::{Observation, ObservationLayout};
+use super::reward::{RewardContext, RewardFn, WinLossReward};
+
+/// Extra information about a step that is not part of the observation.
+#[derive(Debug, Clone)]
+pub struct StepInfo {
+    /// The winner of the round, if the round ended in this step and a side scored more points than
+    /// the other during the round.
+    pub winner: Option<Sides>,
+    /// The scoring events of this step, one for each ball that flew out of the arena.
+    pub events: Vec<ScoringEvent>,
+    /// The scores of both sides after this step.
+    pub scores: (u32, u32),
+    /// The number of steps taken since the last reset.
+    pub step_count: u64,
+    /// The reward of each reward function of the environment, by name. The reward of the step is
+    /// the sum of them.
+    pub reward_components: HashMap<String, f32>,
+}
+
+/// A gym-style environment wrapping a game. An episode is a single round: it starts with a call
+/// to `reset` and is done as soon as the round is over, which depends on the scoring mode of the
+/// game. Unlike the SDL loop in `rust_pong::app::play`, the environment never opens a window, so it
+/// can be driven by a training loop.
+pub struct PongEnv {
+    /// The game being simulated.
+    game: Game,
+    /// The game mode of every episode.
+    game_mode: GameMode,
+    /// The side the agent is playing for. Rewards are given from the perspective of this side.
+    agent_side: Sides,
+    /// The layout of the observations returned by the environment.
+    layout: ObservationLayout,
+    /// The reward functions evaluated after each step.
+    reward_fns: Vec<Box<dyn RewardFn>>,
+    /// Whether the current episode is over.
+    done: bool,
+    /// The number of steps taken since the last reset.
+    step_count: u64,
+}
+
+impl PongEnv {
+    /// The constructor. The agent plays for the left side by default, and is only rewarded for
+    /// winning or losing a round. `reset` has to be called before the first call to `step`.
+    pub fn new() -> Self {
+        Self {
+            game: Game::new(),
+            game_mode: GameMode::Default,
+            agent_side: Sides::Left,
+            layout: ObservationLayout::default(),
+            reward_fns: vec![Box::new(WinLossReward::default())],
+            done: true,
+            step_count: 0,
+        }
+    }
+
+    /// Sets the side the agent is playing for.
+    pub fn set_agent_side(&mut self, side: Sides) {
+        self.agent_side = side;
+    }
+
+    /// Get the side the agent is playing for.
+    pub fn get_agent_side(&self) -> Sides {
+        self.agent_side
+    }
+
+    /// Sets the game mode of the following episodes. The observation layout is changed to have
+    /// exactly enough room for the scene of the game mode. Returns an error if the game mode is a
+    /// custom game with an invalid configuration.
+    pub fn set_game_mode(&mut self, game_mode: GameMode) -> Result<(), ConfigError> {
+        self.layout = match &game_mode {
+            GameMode::Default => ObservationLayout::default(),
+            GameMode::Custom(config) => {
+                config.validate()?;
+                ObservationLayout::new(
+                    config.left_paddles.count,
+                    config.right_paddles.count,
+                    config.balls.count,
+                )
+            }
+        };
+        self.game_mode = game_mode;
+        Ok(())
+    }
+
+    /// Get the game mode of the episodes.
+    pub fn get_game_mode(&self) -> &GameMode {
+        &self.game_mode
+    }
+
+    /// Sets the layout of the observations returned by the environment.
+    pub fn set_observation_layout(&mut self, layout: ObservationLayout) {
+        self.layout = layout;
+    }
+
+    /// Get the layout of the observations returned by the environment.
+    pub fn get_observation_layout(&self) -> &ObservationLayout {
+        &self.layout
+    }
+
+    /// Adds a reward function to the environment.
+    pub fn add_reward_fn(&mut self, reward_fn: Box<dyn RewardFn>) {
+        self.reward_fns.push(reward_fn);
+    }
+
+    /// Removes all reward functions of the environment.
+    pub fn clear_reward_fns(&mut self) {
+        self.reward_fns.clear();
+    }
+
+    /// First removes all reward functions, then adds the provided ones.
+    pub fn reset_reward_fns(&mut self, reward_fns: Vec<Box<dyn RewardFn>>) {
+        self.reward_fns = reward_fns;
+    }
+
+    /// Get the underlying game.
+    pub fn get_game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Whether the current episode is over.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Starts a new episode of the game mode of the environment and returns the first observation.
+    /// If a seed is given, the episode is reproducible: the same seed and the same sequence of
+    /// actions always give the same trajectory. Otherwise the game is seeded from the operating
+    /// system.
+    pub fn reset(&mut self, seed: Option<u64>) -> Observation {
+        self.game = match seed {
+            Some(seed) => Game::with_seed(seed),
+            None => Game::new(),
+        };
+        self.game
+            .start_game(&self.game_mode)
+            .expect("The game mode is validated when it is set.");
+        self.done = false;
+        self.step_count = 0;
+        for reward_fn in self.reward_fns.iter_mut() {
+            reward_fn.reset();
+        }
+        Observation::from_game(&self.game, self.layout)
+    }
+
+    /// Advances the game by one frame given the operations of this frame. Returns the observation
+    /// after the step, the reward of the agent's side, whether the episode is over and some extra
+    /// information. Stepping an episode that is already over does nothing and returns a reward of
+    /// zero.
+    pub fn step(&mut self, actions: &mut Vec<Operation>) -> (Observation, f32, bool, StepInfo) {
+        let mut reward_components = HashMap::new();
+        let mut reward = 0.0;
+        let mut winner = None;
+        let mut events = Vec::new();
+        let mut round_over = false;
+
+        if self.done {
+            actions.clear();
+            for reward_fn in self.reward_fns.iter() {
+                reward_components.insert(reward_fn.name().to_string(), 0.0);
+            }
+        } else {
+            self.step_count += 1;
+            events = self.game.update(actions);
+            round_over = self.game.is_round_over();
+            if round_over {
+                winner = self.game.get_round_winner();
+            }
+
+            let scene = self.game.get_scene();
+            let ctx = RewardContext {
+                scene,
+                winner,
+                round_over,
+                events: &events,
+                hits: scene.get_last_hits(),
+                side: self.agent_side,
+            };
+            for reward_fn in self.reward_fns.iter_mut() {
+                let component = reward_fn.reward(&ctx);
+                reward += component;
+                *reward_components
+                    .entry(reward_fn.name().to_string())
+                    .or_insert(0.0) += component;
+            }
+        }
+
+        self.done = self.done || round_over;
+
+        let info = StepInfo {
+            winner,
+            events,
+            scores: self.game.get_scores(),
+            step_count: self.step_count,
+            reward_components,
+        };
+
+        (
+            Observation::from_game(&self.game, self.layout),
+            reward,
+            self.done,
+            info,
+        )
+    }
+}
+
+impl Default for PongEnv {
+    /// Generate a default environment.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::reward::{PaddleHitReward, SurvivalReward};
+    use crate::game_and_scene::operation::OperationTypes;
+
+    #[test]
+    fn reward_is_the_sum_of_its_components() {
+        let mut env = PongEnv::new();
+        env.add_reward_fn(Box::new(SurvivalReward { per_step: 0.01 }));
+        env.add_reward_fn(Box::new(PaddleHitReward { per_hit: 0.5 }));
+        env.reset(Some(7));
+
+        let mut done = false;
+        while !done {
+            let mut actions = vec![Operation::new(OperationTypes::Up, Sides::Left, 0)];
+            let (_, reward, step_done, info) = env.step(&mut actions);
+            done = step_done;
+
+            let mut names: Vec<&str> = info.reward_components.keys().map(|k| k.as_str()).collect();
+            names.sort();
+            assert_eq!(names, ["paddle_hit", "survival", "win_loss"]);
+            let total: f32 = info.reward_components.values().sum();
+            assert!((reward - total).abs() < 1e-6);
+            if done {
+                assert_eq!(info.reward_components["survival"], 0.0);
+                assert_ne!(info.reward_components["win_loss"], 0.0);
+            } else {
+                assert_eq!(info.reward_components["survival"], 0.01);
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::game_and_scene::scene::{PaddleHit, Scene, ScoringEvent, Sides};
+
+/// The default reward given to the agent's side when it scores.
+pub const DEFAULT_WIN_REWARD: f32 = 1.0;
+/// The default reward given to the agent's side when the other side scores.
+pub const DEFAULT_LOSS_REWARD: f32 = -1.0;
+
+/// Everything a reward function may look at after a step of the environment.
+pub struct RewardContext<'a> {
+    /// The scene after the step.
+    pub scene: &'a Scene,
+    /// The winner of the round, if the round ended in this step and a side scored more points than
+    /// the other during the round.
+    pub winner: Option<Sides>,
+    /// Whether the round ended in this step.
+    pub round_over: bool,
+    /// The scoring events of the step, one for each ball that flew out of the arena.
+    pub events: &'a [ScoringEvent],
+    /// All paddle hits that happened during the step.
+    pub hits: &'a [PaddleHit],
+    /// The side the agent is playing for.
+    pub side: Sides,
+}
+
+/// A reward function is evaluated once per step of the environment. An environment can have
+/// several of them, in which case the reward of a step is the sum of all of them, and each of them
+/// is reported separately under its name.
+pub trait RewardFn: Send {
+    /// The name of the reward function, used as its key in the info of a step.
+    fn name(&self) -> &str;
+
+    /// Computes the reward of a step.
+    fn reward(&mut self, ctx: &RewardContext) -> f32;
+
+    /// Called when the environment is reset. Reward functions that keep track of an episode should
+    /// clear their state here.
+    fn reset(&mut self) {}
+}
+
+/// A sparse reward, only given when a ball flies out of the arena. With the default scoring, a
+/// ball flying out ends the round, so this is a reward for winning or losing the round.
+#[derive(Debug, Clone)]
+pub struct WinLossReward {
+    /// The reward each time the agent's side scores.
+    pub win: f32,
+    /// The reward each time the other side scores.
+    pub loss: f32,
+}
+
+impl Default for WinLossReward {
+    /// Generate a win/loss reward with the default rewards.
+    fn default() -> Self {
+        Self {
+            win: DEFAULT_WIN_REWARD,
+            loss: DEFAULT_LOSS_REWARD,
+        }
+    }
+}
+
+impl RewardFn for WinLossReward {
+    fn name(&self) -> &str {
+        "win_loss"
+    }
+
+    fn reward(&mut self, ctx: &RewardContext) -> f32 {
+        ctx.events
+            .iter()
+            .map(|event| {
+                if event.side == ctx.side {
+                    self.win
+                } else {
+                    self.loss
+                }
+            })
+            .sum()
+    }
+}
+
+/// A reward given each time a ball hits one of the paddles of the agent's side.
+#[derive(Debug, Clone)]
+pub struct PaddleHitReward {
+    /// The reward per hit.
+    pub per_hit: f32,
+}
+
+impl RewardFn for PaddleHitReward {
+    fn name(&self) -> &str {
+        "paddle_hit"
+    }
+
+    fn reward(&mut self, ctx: &RewardContext) -> f32 {
+        let hits = ctx.hits.iter().filter(|hit| hit.side == ctx.side).count();
+        hits as f32 * self.per_hit
+    }
+}
+
+/// A reward given for every step the round goes on.
+#[derive(Debug, Clone)]
+pub struct SurvivalReward {
+    /// The reward per step.
+    pub per_step: f32,
+}
+
+impl RewardFn for SurvivalReward {
+    fn name(&self) -> &str {
+        "survival"
+    }
+
+    fn reward(&mut self, ctx: &RewardContext) -> f32 {
+        if !ctx.round_over {
+            self.per_step
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A shaping reward that penalises the vertical distance between the agent's paddles and the
+/// balls. For each paddle of the agent's side, the distance to the closest ball is divided by the
+/// screen height, and the penalty is the average of these distances multiplied by the scale.
+#[derive(Debug, Clone)]
+pub struct DistanceToBallReward {
+    /// The scale of the penalty. The reward of a step is between `-scale` and 0.
+    pub scale: f32,
+}
+
+impl RewardFn for DistanceToBallReward {
+    fn name(&self) -> &str {
+        "distance_to_ball"
+    }
+
+    fn reward(&mut self, ctx: &RewardContext) -> f32 {
+        let paddles = match ctx.side {
+            Sides::Left => ctx.scene.get_left_paddles(),
+            Sides::Right => ctx.scene.get_right_paddles(),
+        };
+        let balls = ctx.scene.get_balls();
+        if paddles.is_empty() || balls.is_empty() {
+            return 0.0;
+        }
+
+        let total_distance: f32 = paddles
+            .iter()
+            .map(|paddle| {
+                balls
+                    .iter()
+                    .map(|ball| (paddle.get_pos().y - ball.get_pos().y).abs())
+                    .fold(f32::INFINITY, f32::min)
+            })
+            .sum();
+
+        -self.scale * total_distance / (paddles.len() as f32 * ctx.scene.get_arena().height_f32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_and_scene::{arena::ArenaConfig, ball::Ball, paddle::Paddle};
+    use crate::math_utils::vec2::Vec2;
+
+    /// A scene with the default left paddle in the middle of the left side, and a ball 60 pixels
+    /// below it.
+    fn scene() -> Scene {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![Paddle::default_left_paddle(&arena)]);
+        scene.add_balls(vec![Ball::new(
+            Vec2::new(arena.center().x, arena.center().y + 60.0),
+            Vec2::new(0.0, 0.0),
+            3,
+        )]);
+        scene
+    }
+
+    /// The context of a step in which the left side scored twice and the right side once, and a
+    /// ball hit a paddle of each side.
+    fn context(scene: &Scene, round_over: bool) -> RewardContext<'_> {
+        const EVENTS: &[ScoringEvent] = &[
+            ScoringEvent {
+                side: Sides::Left,
+                ball_index: 0,
+            },
+            ScoringEvent {
+                side: Sides::Right,
+                ball_index: 1,
+            },
+            ScoringEvent {
+                side: Sides::Left,
+                ball_index: 2,
+            },
+        ];
+        const HITS: &[PaddleHit] = &[
+            PaddleHit {
+                side: Sides::Left,
+                paddle_index: 0,
+                ball_index: 0,
+            },
+            PaddleHit {
+                side: Sides::Right,
+                paddle_index: 1,
+                ball_index: 1,
+            },
+        ];
+        RewardContext {
+            scene,
+            winner: round_over.then_some(Sides::Left),
+            round_over,
+            events: EVENTS,
+            hits: HITS,
+            side: Sides::Left,
+        }
+    }
+
+    #[test]
+    fn win_loss_reward_counts_every_scoring_event() {
+        let scene = scene();
+        let mut reward = WinLossReward {
+            win: 1.0,
+            loss: -0.5,
+        };
+
+        assert_eq!(reward.reward(&context(&scene, true)), 1.5);
+    }
+
+    #[test]
+    fn paddle_hit_reward_only_counts_the_hits_of_the_agent() {
+        let scene = scene();
+        let mut reward = PaddleHitReward { per_hit: 0.25 };
+
+        assert_eq!(reward.reward(&context(&scene, false)), 0.25);
+    }
+
+    #[test]
+    fn survival_reward_stops_when_the_round_is_over() {
+        let scene = scene();
+        let mut reward = SurvivalReward { per_step: 0.01 };
+
+        assert_eq!(reward.reward(&context(&scene, false)), 0.01);
+        assert_eq!(reward.reward(&context(&scene, true)), 0.0);
+    }
+
+    #[test]
+    fn distance_to_ball_reward_is_scaled_by_the_screen_height() {
+        let scene = scene();
+        let mut reward = DistanceToBallReward { scale: 2.0 };
+
+        let value = reward.reward(&context(&scene, false));
+
+        assert!((value - -2.0 * 60.0 / 600.0).abs() < 1e-6);
+        let mut context = context(&scene, false);
+        context.side = Sides::Right;
+        assert_eq!(reward.reward(&context), 0.0);
+    }
+}
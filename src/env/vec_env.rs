@@ -0,0 +1,259 @@
+use crate::game_and_scene::operation::Operation;
+
+use super::observation::{Observation, ObservationLayout};
+use super::pong_env::{PongEnv, StepInfo};
+
+/// The result of stepping all environments of a `VecPongEnv` once. The values of environment `i`
+/// are at index `i` of each vector, except for the observations, which are stacked into a single
+/// vector where environment `i` takes the values from `i * layout.len()` to
+/// `(i + 1) * layout.len()`.
+#[derive(Debug, Clone)]
+pub struct VecStepResult {
+    /// The stacked observations of all environments. The observation of an environment whose
+    /// episode ended in this step is the first observation of its next episode.
+    pub observations: Vec<f32>,
+    /// The rewards of all environments.
+    pub rewards: Vec<f32>,
+    /// Whether the episode of each environment ended in this step.
+    pub dones: Vec<bool>,
+    /// The extra information of each environment.
+    pub infos: Vec<StepInfo>,
+    /// The last observation of each episode that ended in this step, since it is replaced by the
+    /// first observation of the next episode in `observations`.
+    pub terminal_observations: Vec<Option<Observation>>,
+}
+
+/// The result of stepping a single environment, before the results of all environments are
+/// stacked.
+type SingleStepResult = (Observation, f32, bool, StepInfo, Option<Observation>);
+
+/// A vectorised environment stepping many games in lockstep. Every environment whose episode ends
+/// is reset automatically, so the caller never has to reset a single environment. With the
+/// `parallel` feature, the environments can be stepped on a thread pool.
+pub struct VecPongEnv {
+    /// All environments.
+    envs: Vec<PongEnv>,
+    /// The seed passed to the last call to `reset`, if any.
+    seed: Option<u64>,
+    /// The number of episodes each environment has finished since the last call to `reset`.
+    episode_counts: Vec<u64>,
+    /// The thread pool the environments are stepped on. If there is none, they are stepped one
+    /// after another on the current thread.
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl VecPongEnv {
+    /// Constructs a vectorised environment of the given number of default environments.
+    pub fn new(num_envs: usize) -> Self {
+        Self::from_envs((0..num_envs).map(|_| PongEnv::new()).collect())
+    }
+
+    /// Constructs a vectorised environment from the given environments. They should all have the
+    /// same observation layout, otherwise the stacked observations cannot be split again.
+    pub fn from_envs(envs: Vec<PongEnv>) -> Self {
+        let episode_counts = vec![0; envs.len()];
+        Self {
+            envs,
+            seed: None,
+            episode_counts,
+            #[cfg(feature = "parallel")]
+            pool: None,
+        }
+    }
+
+    /// Steps the environments on a thread pool with the given number of threads. If the number is
+    /// 0, rayon chooses it according to the number of cores.
+    #[cfg(feature = "parallel")]
+    pub fn set_num_threads(&mut self, num_threads: usize) -> Result<(), String> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|e| format!("Error: failed to build the thread pool: {e}"))?;
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    /// Steps the environments one after another on the current thread again.
+    #[cfg(feature = "parallel")]
+    pub fn set_single_threaded(&mut self) {
+        self.pool = None;
+    }
+
+    /// Get the number of environments.
+    pub fn get_num_envs(&self) -> usize {
+        self.envs.len()
+    }
+
+    /// Get all environments.
+    pub fn get_envs(&self) -> &Vec<PongEnv> {
+        &self.envs
+    }
+
+    /// Sets the observation layout of all environments.
+    pub fn set_observation_layout(&mut self, layout: ObservationLayout) {
+        for env in self.envs.iter_mut() {
+            env.set_observation_layout(layout);
+        }
+    }
+
+    /// The number of values in the observation of a single environment.
+    pub fn get_observation_len(&self) -> usize {
+        self.envs
+            .first()
+            .map_or(0, |env| env.get_observation_layout().len())
+    }
+
+    /// Resets all environments and returns their stacked first observations. If a seed is given,
+    /// environment `i` is seeded with `seed + i`, and the episodes started by automatic resets
+    /// are seeded as well, so that the whole run is reproducible.
+    pub fn reset(&mut self, seed: Option<u64>) -> Vec<f32> {
+        self.seed = seed;
+        self.episode_counts.iter_mut().for_each(|count| *count = 0);
+
+        let num_envs = self.envs.len() as u64;
+        let mut observations = Vec::with_capacity(self.envs.len() * self.get_observation_len());
+        for (i, env) in self.envs.iter_mut().enumerate() {
+            let seed = episode_seed(seed, i as u64, 0, num_envs);
+            observations.extend(env.reset(seed).into_values());
+        }
+        observations
+    }
+
+    /// Steps every environment with its own operations. `actions[i]` are the operations of
+    /// environment `i`, so there must be exactly one vector of operations per environment.
+    pub fn step(&mut self, actions: &mut [Vec<Operation>]) -> VecStepResult {
+        assert_eq!(
+            actions.len(),
+            self.envs.len(),
+            "Error: there must be one vector of operations per environment."
+        );
+
+        let seed = self.seed;
+        let num_envs = self.envs.len() as u64;
+        let step_one = |(i, ((env, episode_count), ops)): (
+            usize,
+            ((&mut PongEnv, &mut u64), &mut Vec<Operation>),
+        )|
+         -> SingleStepResult {
+            let (observation, reward, done, info) = env.step(ops);
+            if done {
+                *episode_count += 1;
+                let seed = episode_seed(seed, i as u64, *episode_count, num_envs);
+                let first_observation = env.reset(seed);
+                (first_observation, reward, done, info, Some(observation))
+            } else {
+                (observation, reward, done, info, None)
+            }
+        };
+
+        let results: Vec<SingleStepResult> = {
+            let zipped = self
+                .envs
+                .iter_mut()
+                .zip(self.episode_counts.iter_mut())
+                .zip(actions.iter_mut());
+
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+
+                let zipped: Vec<_> = zipped.collect();
+                match &self.pool {
+                    Some(pool) => {
+                        pool.install(|| zipped.into_par_iter().enumerate().map(step_one).collect())
+                    }
+                    None => zipped.into_iter().enumerate().map(step_one).collect(),
+                }
+            }
+
+            #[cfg(not(feature = "parallel"))]
+            {
+                zipped.enumerate().map(step_one).collect()
+            }
+        };
+
+        let mut step_result = VecStepResult {
+            observations: Vec::with_capacity(self.envs.len() * self.get_observation_len()),
+            rewards: Vec::with_capacity(self.envs.len()),
+            dones: Vec::with_capacity(self.envs.len()),
+            infos: Vec::with_capacity(self.envs.len()),
+            terminal_observations: Vec::with_capacity(self.envs.len()),
+        };
+        for (observation, reward, done, info, terminal_observation) in results {
+            step_result.observations.extend(observation.into_values());
+            step_result.rewards.push(reward);
+            step_result.dones.push(done);
+            step_result.infos.push(info);
+            step_result.terminal_observations.push(terminal_observation);
+        }
+        step_result
+    }
+}
+
+/// The seed of an episode of an environment, given the seed passed to `VecPongEnv::reset`. Every
+/// episode of every environment gets a different seed.
+fn episode_seed(seed: Option<u64>, env_index: u64, episode: u64, num_envs: u64) -> Option<u64> {
+    seed.map(|seed| {
+        seed.wrapping_add(env_index)
+            .wrapping_add(episode.wrapping_mul(num_envs))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_and_scene::{operation::OperationTypes, scene::Sides};
+
+    /// The operations of every environment in a step, moving the first left paddle up.
+    fn actions(num_envs: usize) -> Vec<Vec<Operation>> {
+        (0..num_envs)
+            .map(|_| vec![Operation::new(OperationTypes::Up, Sides::Left, 0)])
+            .collect()
+    }
+
+    #[test]
+    fn finished_episodes_are_reset_with_their_own_seed() {
+        let mut vec_env = VecPongEnv::new(2);
+        let len = vec_env.get_observation_len();
+        let first = vec_env.reset(Some(10));
+        assert_eq!(&first[..len], PongEnv::new().reset(Some(10)).get_values());
+        assert_eq!(&first[len..], PongEnv::new().reset(Some(11)).get_values());
+
+        let mut episodes = [0_u64; 2];
+        for _ in 0..10_000 {
+            let result = vec_env.step(&mut actions(2));
+            for (i, episode) in episodes.iter_mut().enumerate() {
+                assert_eq!(result.dones[i], result.terminal_observations[i].is_some());
+                if !result.dones[i] {
+                    continue;
+                }
+                // environment i of 2 is seeded with 10 + i + 2 * episode
+                *episode += 1;
+                let seed = 10 + i as u64 + 2 * *episode;
+                assert_eq!(
+                    &result.observations[i * len..(i + 1) * len],
+                    PongEnv::new().reset(Some(seed)).get_values()
+                );
+            }
+            if episodes.iter().all(|&episode| episode >= 2) {
+                return;
+            }
+        }
+        panic!("the episodes never ended: {episodes:?}");
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let run = || {
+            let mut vec_env = VecPongEnv::new(3);
+            let mut observations = vec_env.reset(Some(5));
+            for _ in 0..1000 {
+                observations.extend(vec_env.step(&mut actions(3)).observations);
+            }
+            observations
+        };
+
+        assert_eq!(run(), run());
+    }
+}
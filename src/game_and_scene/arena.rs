@@ -0,0 +1,84 @@
+use serde::Deserialize;
+
+use super::paddle::DEFAULT_PADDLE_WIDTH;
+use super::scene::Sides;
+use crate::math_utils::vec2::Vec2;
+
+/// The default arena width.
+pub const DEFAULT_ARENA_WIDTH: u32 = 800;
+/// The default arena height.
+pub const DEFAULT_ARENA_HEIGHT: u32 = 600;
+/// The default margin on the left and the right of the arena. This is the distance between the
+/// left/right edge of the left/right paddles and the left/right edges.
+pub const DEFAULT_PADDLE_MARGIN: u32 = DEFAULT_PADDLE_WIDTH;
+
+/// The dimensions of the arena a scene is played in. Every scene owns its arena, so scenes of
+/// different sizes can be simulated side by side. The unit is pixels, and the origin is the top
+/// left corner of the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ArenaConfig {
+    /// The width of the arena.
+    pub width: u32,
+    /// The height of the arena.
+    pub height: u32,
+    /// The distance between the outer edge of the paddles and the left/right edge of the arena.
+    pub paddle_margin: u32,
+}
+
+impl ArenaConfig {
+    /// Constructs an arena of the given size with the default paddle margin.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            paddle_margin: DEFAULT_PADDLE_MARGIN,
+        }
+    }
+
+    /// The width of the arena as a float.
+    pub fn width_f32(&self) -> f32 {
+        self.width as f32
+    }
+
+    /// The height of the arena as a float.
+    pub fn height_f32(&self) -> f32 {
+        self.height as f32
+    }
+
+    /// The center of the arena, where balls are launched from.
+    pub fn center(&self) -> Vec2 {
+        Vec2::new((self.width / 2) as f32, (self.height / 2) as f32)
+    }
+
+    /// The x position of the center of a paddle of the given width on the given side.
+    pub fn paddle_center_x(&self, side: Sides, paddle_width: u32) -> f32 {
+        match side {
+            Sides::Left => (self.paddle_margin + paddle_width / 2) as f32,
+            Sides::Right => (self.width - self.paddle_margin - paddle_width / 2) as f32,
+        }
+    }
+
+    /// The vertical band of the arena given to the paddle of the given index when there are
+    /// `count` paddles on its side. The height is split into `count` bands of the same size, from
+    /// top to bottom. Returns the top and the bottom of the band.
+    pub fn paddle_lane(&self, index: usize, count: usize) -> (f32, f32) {
+        let lane_height = self.height_f32() / count as f32;
+        (index as f32 * lane_height, (index + 1) as f32 * lane_height)
+    }
+
+    /// The initial y position of the center of the paddle of the given index when there are
+    /// `count` paddles on its side, which is the middle of its lane. The paddles of a side are
+    /// spread evenly over the height of the arena, so that they do not overlap.
+    pub fn paddle_center_y(&self, index: usize, count: usize) -> f32 {
+        let (top, bottom) = self.paddle_lane(index, count);
+        (top + bottom) / 2.0
+    }
+}
+
+impl Default for ArenaConfig {
+    /// The arena of the default game.
+    fn default() -> Self {
+        Self::new(DEFAULT_ARENA_WIDTH, DEFAULT_ARENA_HEIGHT)
+    }
+}
@@ -1,51 +1,111 @@
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::paddle::Paddle;
+use super::arena::{ArenaConfig, DEFAULT_ARENA_WIDTH};
+use super::paddle::{Paddle, PaddleRegion};
 use super::scene::Edges;
-use crate::math_utils::vec2::Vec2;
-
-use super::paddle::DEFAULT_PADDLE_WIDTH;
-use super::scene::PADDLE_MARGIN;
-use super::scene::SCREEN_HEIGHT;
-use super::scene::SCREEN_WIDTH;
+use super::timestep::{TIME_STEP, UPDATES_PER_SECOND};
+use crate::math_utils::{
+    collision::{overlap_normal, sweep_circle_aabb, sweep_circle_circle, Aabb, SweepHit},
+    vec2::{Vec2, DEFAULT_ANGLE_RANGE},
+};
 
 /// The default radius of the ball. The unit is in pixels.
 pub const DEFAULT_RADIUS: u32 = 3;
-/// The default speed of the ball. The unit is in pixels per frame.
-pub const DEFAULT_BALL_SPEED: f32 = (SCREEN_WIDTH / 200) as f32;
+/// The default speed of the ball. The unit is in pixels per second.
+pub const DEFAULT_BALL_SPEED: f32 = (DEFAULT_ARENA_WIDTH / 200 * UPDATES_PER_SECOND) as f32;
+/// The default maximal angle between the horizontal and the direction of a ball bouncing off a
+/// paddle, in radians. It is reached when the ball hits the very end of the paddle.
+pub const DEFAULT_MAX_DEFLECTION: f32 = DEFAULT_ANGLE_RANGE;
+/// The default maximal speed a ball reaches by speeding up on paddle hits. The unit is pixels per
+/// second.
+pub const DEFAULT_MAX_BALL_SPEED: f32 = DEFAULT_BALL_SPEED * 4.0;
 
 /// The ball struct.
 pub struct Ball {
     /// The position of the center of the ball.
     pos: Vec2,
-    /// The velocity of the ball. The unit is pixels per frame.
+    /// The position of the center of the ball before the last update, to interpolate between the
+    /// last two updates when rendering.
+    prev_pos: Vec2,
+    /// The velocity of the ball. The unit is pixels per second.
     vel: Vec2,
     /// The radius of the ball. The unit is pixels.
     radius: u32,
+    /// The maximal angle between the horizontal and the direction of the ball after it bounces off
+    /// a paddle, in radians.
+    max_deflection: f32,
+    /// The factor the speed of the ball is multiplied by each time it bounces off a paddle. A
+    /// factor of 1 keeps the speed constant.
+    speed_up_factor: f32,
+    /// The speed the ball cannot exceed by speeding up. The unit is pixels per second.
+    max_speed: f32,
+    /// The fraction of the vertical velocity of a paddle that is added to the vertical velocity
+    /// of a ball bouncing off it. With 0, the motion of the paddle does not matter.
+    paddle_vel_transfer: f32,
 }
 
 impl Ball {
     /// The constructor.
     pub fn new(pos: Vec2, vel: Vec2, radius: u32) -> Self {
-        Self { pos, vel, radius }
+        Self {
+            prev_pos: pos.clone(),
+            pos,
+            vel,
+            radius,
+            max_deflection: DEFAULT_MAX_DEFLECTION,
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_vel_transfer: 0.0,
+        }
     }
 
     /// Generate a new ball given its velocity. The generated ball will be located at the center of
-    /// the screen and has the default radius.
-    pub fn generate_with_vel(vel: Vec2) -> Self {
+    /// the arena and has the default radius.
+    pub fn generate_with_vel(vel: Vec2, arena: &ArenaConfig) -> Self {
         Self {
-            pos: Vec2::default(),
+            pos: arena.center(),
+            prev_pos: arena.center(),
             vel,
             radius: DEFAULT_RADIUS,
+            max_deflection: DEFAULT_MAX_DEFLECTION,
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_vel_transfer: 0.0,
         }
     }
 
     /// Generate a new ball as generate_with_vel, but the velocity is random instead.
-    pub fn random_centered_ball(rng: &mut ThreadRng) -> Self {
+    pub fn random_centered_ball<R: Rng + ?Sized>(arena: &ArenaConfig, rng: &mut R) -> Self {
         Self {
-            pos: Vec2::default(),
+            pos: arena.center(),
+            prev_pos: arena.center(),
             vel: Vec2::random_with_magnitude(DEFAULT_BALL_SPEED, None, rng),
             radius: DEFAULT_RADIUS,
+            max_deflection: DEFAULT_MAX_DEFLECTION,
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_vel_transfer: 0.0,
+        }
+    }
+
+    /// Same as random_centered_ball, but with the given speed, radius and range of launching
+    /// angles instead of the default ones. See `DEFAULT_ANGLE_RANGE` for the meaning of the range.
+    pub fn random_centered_ball_with<R: Rng + ?Sized>(
+        arena: &ArenaConfig,
+        speed: f32,
+        radius: u32,
+        angle_range: f32,
+        rng: &mut R,
+    ) -> Self {
+        Self {
+            pos: arena.center(),
+            prev_pos: arena.center(),
+            vel: Vec2::random_with_magnitude(speed, Some(angle_range), rng),
+            radius,
+            max_deflection: DEFAULT_MAX_DEFLECTION,
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_vel_transfer: 0.0,
         }
     }
 
@@ -64,37 +124,136 @@ impl Ball {
         self.radius
     }
 
-    /// Update the ball after it bounces with a paddle or an edge.
-    pub fn bounce_after_collision(&mut self, collision: Collision) {
+    /// Get the factor the speed of the ball is multiplied by each time it bounces off a paddle.
+    pub fn get_speed_up_factor(&self) -> f32 {
+        self.speed_up_factor
+    }
+
+    /// Sets the factor the speed of the ball is multiplied by each time it bounces off a paddle,
+    /// and the speed it cannot exceed by speeding up.
+    pub fn set_speed_up(&mut self, factor: f32, max_speed: f32) {
+        self.speed_up_factor = factor;
+        self.max_speed = max_speed;
+    }
+
+    /// Get the speed the ball cannot exceed by speeding up.
+    pub fn get_max_speed(&self) -> f32 {
+        self.max_speed
+    }
+
+    /// Get the fraction of the vertical velocity of a paddle that is added to a ball bouncing off
+    /// it.
+    pub fn get_paddle_vel_transfer(&self) -> f32 {
+        self.paddle_vel_transfer
+    }
+
+    /// Sets the fraction of the vertical velocity of a paddle that is added to a ball bouncing off
+    /// it.
+    pub fn set_paddle_vel_transfer(&mut self, paddle_vel_transfer: f32) {
+        self.paddle_vel_transfer = paddle_vel_transfer;
+    }
+
+    /// Get the maximal angle between the horizontal and the direction of the ball after it bounces
+    /// off a paddle, in radians.
+    pub fn get_max_deflection(&self) -> f32 {
+        self.max_deflection
+    }
+
+    /// Sets the maximal angle between the horizontal and the direction of the ball after it
+    /// bounces off a paddle, in radians.
+    pub fn set_max_deflection(&mut self, max_deflection: f32) {
+        self.max_deflection = max_deflection;
+    }
+
+    /// Update the ball after it bounces with a paddle or an edge of the given arena.
+    ///
+    /// How the ball bounces off a paddle depends on the region of the paddle it touches. Off the
+    /// front face, it is deflected depending on where it hit, see `deflect_off`. Off the back,
+    /// top or bottom face, it is reflected by the face, and off a corner, it is reflected along
+    /// the contact normal, so that it comes off diagonally.
+    pub fn bounce_after_collision(&mut self, collision: Collision, arena: &ArenaConfig) {
         use Collision::*;
         use Edges::{Bottom, Top};
-        match collision {
+        match &collision {
             WithEdge(Top) | WithEdge(Bottom) => self.vel.y *= -1.0,
-            WithPaddle(_) => self.vel.x *= -1.0,
+            WithPaddle(paddle, normal) => match paddle.contact_region(normal) {
+                PaddleRegion::Front => self.deflect_off(paddle),
+                PaddleRegion::Back => self.vel.x = normal.x * self.vel.x.abs(),
+                PaddleRegion::Top => self.vel.y = (-self.vel.y.abs()).min(paddle.get_vel()),
+                PaddleRegion::Bottom => self.vel.y = self.vel.y.abs().max(paddle.get_vel()),
+                PaddleRegion::Corner => {
+                    let along_normal = self.vel.dot(normal);
+                    if along_normal < 0.0 {
+                        self.vel -= &(normal * (2.0 * along_normal));
+                    }
+                }
+            },
             _ => {}
         }
-        self.reset_pos(&collision);
+        self.reset_pos(&collision, arena);
+    }
+
+    /// Sends the ball away from a paddle it hit. The direction of the ball depends on where it
+    /// hit the paddle: a ball hitting the center of the paddle leaves horizontally, and the
+    /// further from the center it hits, the larger the angle, up to `max_deflection` at the ends
+    /// of the paddle. A ball hitting the lower half goes down, and a ball hitting the upper half
+    /// goes up.
+    ///
+    /// The speed of the ball is then multiplied by `speed_up_factor`, without exceeding
+    /// `max_speed`, and a fraction of the vertical velocity of the paddle is added to the vertical
+    /// velocity of the ball, so a moving paddle can put some english on the ball.
+    fn deflect_off(&mut self, paddle: &Paddle) {
+        let reach = (paddle.get_height() / 2) as f32 + self.radius as f32;
+        let offset = ((self.pos.y - paddle.get_pos().y) / reach).clamp(-1.0, 1.0);
+        let angle = offset * self.max_deflection;
+        let direction = if paddle.is_left() { 1.0 } else { -1.0 };
+
+        // a ball launched faster than the cap is not slowed down
+        let speed = self.vel.magnitude();
+        let max_speed = self.max_speed.max(speed);
+        let speed = (speed * self.speed_up_factor).min(max_speed);
+
+        self.vel = Vec2::new(direction * speed * angle.cos(), speed * angle.sin());
+        self.vel.y += self.paddle_vel_transfer * paddle.get_vel();
+        let magnitude = self.vel.magnitude();
+        if magnitude > max_speed {
+            self.vel *= max_speed / magnitude;
+        }
     }
 
     /// Resets the position of the ball after bouncing to prevent bugs. By bugs, I mean that since
-    /// a collision is detected when the ball and the paddle (or edge) overlap, part of the ball
+    /// a collision can be detected when the ball and the paddle (or edge) overlap, part of the ball
     /// may still be inside the paddle (or out of the edge) after changing its direction, in which
     /// case the ball will keep changing its direction. Therefore, we need to move it outside the
-    /// paddle (or inside the edge), so that it will only change its direction once.
-    fn reset_pos(&mut self, collision: &Collision) {
+    /// paddle (or inside the edge), so that it will only change its direction once. The ball is
+    /// only pushed out along the contact normal, so a ball touching the top of a paddle is not
+    /// moved sideways.
+    fn reset_pos(&mut self, collision: &Collision, arena: &ArenaConfig) {
         use Collision::*;
         match collision {
-            WithPaddle(paddle) => {
-                if paddle.is_left() {
-                    self.pos.x = (PADDLE_MARGIN + DEFAULT_PADDLE_WIDTH + self.radius) as f32;
-                } else {
-                    self.pos.x =
-                        (SCREEN_WIDTH - PADDLE_MARGIN - DEFAULT_PADDLE_WIDTH - self.radius) as f32
+            WithPaddle(paddle, normal) => {
+                let aabb = paddle.get_aabb();
+                let radius = self.radius as f32;
+                match paddle.contact_region(normal) {
+                    PaddleRegion::Front | PaddleRegion::Back if normal.x > 0.0 => {
+                        self.pos.x = self.pos.x.max(aabb.max.x + radius)
+                    }
+                    PaddleRegion::Front | PaddleRegion::Back => {
+                        self.pos.x = self.pos.x.min(aabb.min.x - radius)
+                    }
+                    PaddleRegion::Top => self.pos.y = self.pos.y.min(aabb.min.y - radius),
+                    PaddleRegion::Bottom => self.pos.y = self.pos.y.max(aabb.max.y + radius),
+                    PaddleRegion::Corner => {
+                        let corner = aabb.closest_point(&self.pos);
+                        if (&self.pos - &corner).magnitude() < radius {
+                            self.pos = &corner + &(normal * radius);
+                        }
+                    }
                 }
             }
             _ => {
-                if self.pos.y + self.radius as f32 > SCREEN_HEIGHT as f32 {
-                    self.pos.y = (SCREEN_HEIGHT - self.radius) as f32;
+                if self.pos.y + self.radius as f32 > arena.height_f32() {
+                    self.pos.y = (arena.height - self.radius) as f32;
                 } else if (self.pos.y - self.radius as f32) < 0.0 {
                     self.pos.y = self.radius as f32;
                 }
@@ -102,58 +261,324 @@ impl Ball {
         };
     }
 
-    /// Updates its position according to its velocity.
+    /// Get the position of the center of the ball at the given fraction of the way from its
+    /// position before the last update to its current position.
+    pub fn get_interpolated_pos(&self, alpha: f32) -> Vec2 {
+        &self.prev_pos + &(&(&self.pos - &self.prev_pos) * alpha)
+    }
+
+    /// Remembers the current position as the position before the update that is about to run.
+    pub fn save_pos(&mut self) {
+        self.prev_pos = self.pos.clone();
+    }
+
+    /// Updates its position according to its velocity over a whole update.
     pub fn update_pos(&mut self) {
-        self.pos += &self.vel;
+        self.advance(1.0);
     }
 
-    /// Detects collision with a paddle.
-    pub fn collides_with<'a>(&self, paddle: &'a Paddle) -> Option<Collision<'a>> {
-        use Collision::WithPaddle;
-
-        let &Vec2 {
-            x: paddle_pos_x,
-            y: paddle_pos_y,
-        } = paddle.get_pos();
-        let half_height: f32 = (paddle.get_height() / 2) as f32;
-        let half_width: f32 = (paddle.get_width() / 2) as f32;
-        let radius_as_f32 = self.radius as f32;
-
-        // here, bounds mean the bounds of the y-values, not what we see on the screen
-        // upper bound means that y-value shouldn't be greater than this value
-        // lower bound means that y-value shouldn't be less than this value
-        // on screen, upper bound is actually beneath lower bound
-        let y_lower_bound: f32 = paddle_pos_y - half_height - radius_as_f32;
-        let y_upper_bound: f32 = paddle_pos_y + half_height + radius_as_f32;
-
-        let x_lower_bound: f32 = paddle_pos_x - half_width;
-        let x_upper_bound: f32 = paddle_pos_x + half_width;
-
-        if self.pos.y > y_lower_bound
-            && self.pos.y < y_upper_bound
-            && self.pos.x > x_lower_bound
-            && self.pos.x < x_upper_bound
-        {
-            Some(WithPaddle(paddle))
+    /// The motion of the ball along its velocity during the given fraction of an update.
+    fn motion(&self, fraction: f32) -> Vec2 {
+        &self.vel * (TIME_STEP * fraction)
+    }
+
+    /// Moves the ball along its velocity for the given fraction of an update.
+    pub fn advance(&mut self, fraction: f32) {
+        self.pos += &self.motion(fraction);
+    }
+
+    /// Finds when the ball first touches the top or the bottom edge of the arena while moving
+    /// along its velocity for the given fraction of an update. Returns the time of impact as a
+    /// fraction of that motion, and the edge.
+    pub fn sweep_edges(&self, arena: &ArenaConfig, fraction: f32) -> Option<(f32, Edges)> {
+        let motion_y = self.motion(fraction).y;
+        let radius = self.radius as f32;
+        let (distance, edge) = if motion_y < 0.0 {
+            (self.pos.y - radius, Edges::Top)
+        } else if motion_y > 0.0 {
+            (arena.height_f32() - radius - self.pos.y, Edges::Bottom)
+        } else {
+            return None;
+        };
+
+        let t = (distance / motion_y.abs()).max(0.0);
+        if t <= 1.0 {
+            Some((t, edge))
         } else {
             None
         }
     }
+
+    /// Finds when the ball first touches the paddle while moving along its velocity for the given
+    /// fraction of an update, with a swept test so that fast balls cannot pass through thin
+    /// paddles. The contact normal of the hit tells which region of the paddle is touched.
+    pub fn sweep_paddle(&self, paddle: &Paddle, fraction: f32) -> Option<SweepHit> {
+        let motion = self.motion(fraction);
+        sweep_circle_aabb(&self.pos, self.radius as f32, &motion, &paddle.get_aabb())
+    }
+
+    /// Finds when the ball first touches another ball, given where both balls were at the same
+    /// earlier time of this update. Both balls are assumed to have moved in a straight line from
+    /// there to their current position. Returns the time of impact as a fraction of that motion.
+    pub fn sweep_ball(&self, start: &Vec2, other: &Ball, other_start: &Vec2) -> Option<f32> {
+        sweep_circle_circle(
+            start,
+            self.radius as f32,
+            &(&self.pos - start),
+            other_start,
+            other.radius as f32,
+            &(&other.pos - other_start),
+        )
+        .map(|hit| hit.t)
+    }
+
+    /// Bounces two balls off each other, given where both balls were at the same earlier time of
+    /// this update and the time of impact returned by `sweep_ball` for these positions. Both balls
+    /// are moved back to where they touch, and stay there for the rest of the update. Returns
+    /// whether they bounced, which they do not if they already move apart.
+    ///
+    /// The balls exchange momentum along the line between their centers, the mass of a ball being
+    /// its area. With a restitution of 1, the collision is elastic and no energy is lost, and with
+    /// a restitution of 0, the balls stop moving apart along that line.
+    pub fn bounce_off_ball(
+        &mut self,
+        start: &Vec2,
+        other: &mut Ball,
+        other_start: &Vec2,
+        t: f32,
+        restitution: f32,
+    ) -> bool {
+        let contact = start + &(&(&self.pos - start) * t);
+        let other_contact = other_start + &(&(&other.pos - other_start) * t);
+        let offset = &contact - &other_contact;
+        let distance = offset.magnitude();
+        if distance == 0.0 {
+            return false;
+        }
+        let normal = &offset / distance;
+        let approach = (&self.vel - &other.vel).dot(&normal);
+        if approach >= 0.0 {
+            return false;
+        }
+
+        // the inverse masses, so that the lighter ball changes its velocity the most
+        let inv_mass = 1.0 / (self.radius * self.radius) as f32;
+        let other_inv_mass = 1.0 / (other.radius * other.radius) as f32;
+        let impulse = -(1.0 + restitution) * approach / (inv_mass + other_inv_mass);
+
+        let speeds = (self.vel.magnitude(), other.vel.magnitude());
+        self.vel += &(&normal * (impulse * inv_mass));
+        other.vel -= &(&normal * (impulse * other_inv_mass));
+        self.clamp_speed(speeds.0);
+        other.clamp_speed(speeds.1);
+
+        // balls that already overlapped are pushed apart, the lighter one the furthest
+        let overlap = (self.radius + other.radius) as f32 - distance;
+        self.pos = contact;
+        other.pos = other_contact;
+        if overlap > 0.0 {
+            let share = inv_mass / (inv_mass + other_inv_mass);
+            self.pos += &(&normal * (overlap * share));
+            other.pos -= &(&normal * (overlap * (1.0 - share)));
+        }
+        true
+    }
+
+    /// Pushes the ball out of the way of a paddle that moved onto it during the update. Sweeping
+    /// the motion of the ball only catches the paddles the ball moves into, so a paddle running
+    /// into a ball, for example a paddle moving down onto a ball below it, is handled here, after
+    /// the paddles moved and before the balls do. Returns whether the paddle touched the ball.
+    ///
+    /// The motion of the paddle is swept against the ball, seen from the paddle, to find where it
+    /// touched the ball. The ball is then moved along the contact normal by the rest of the motion
+    /// of the paddle, so that it touches the paddle where the paddle ended up, and it bounces off
+    /// the paddle, leaving at least as fast as the paddle moves along the normal.
+    pub fn get_pushed_by(&mut self, paddle: &Paddle) -> bool {
+        let motion = paddle.get_motion();
+        if motion == Vec2::default() {
+            return false;
+        }
+        let start = Aabb::from_center(
+            &(paddle.get_pos() - &motion),
+            paddle.get_width() as f32,
+            paddle.get_height() as f32,
+        );
+        let Some(hit) = sweep_circle_aabb(&self.pos, self.radius as f32, &(&motion * -1.0), &start)
+        else {
+            return false;
+        };
+
+        let normal = hit.normal;
+        let push = (&motion * (1.0 - hit.t)).dot(&normal).max(0.0);
+        self.pos += &(&normal * push);
+
+        let speed = self.vel.magnitude();
+        let along_normal = self.vel.dot(&normal);
+        if along_normal < 0.0 {
+            self.vel -= &(&normal * (2.0 * along_normal));
+        }
+        let paddle_along_normal = paddle.get_vel() * normal.y;
+        let along_normal = self.vel.dot(&normal);
+        if along_normal < paddle_along_normal {
+            self.vel += &(&normal * (paddle_along_normal - along_normal));
+        }
+        self.clamp_speed(speed);
+        true
+    }
+
+    /// Slows the ball down to its maximal speed, or to the given speed it had before if it was
+    /// already faster than that.
+    fn clamp_speed(&mut self, previous_speed: f32) {
+        let max_speed = self.max_speed.max(previous_speed);
+        let speed = self.vel.magnitude();
+        if speed > max_speed {
+            self.vel *= max_speed / speed;
+        }
+    }
+
+    /// Detects an overlap with a paddle. The collision carries the contact normal, pointing from
+    /// the paddle towards the ball, which tells which region of the paddle is touched.
+    pub fn collides_with<'a>(&self, paddle: &'a Paddle) -> Option<Collision<'a>> {
+        overlap_normal(&self.pos, self.radius as f32, &paddle.get_aabb())
+            .map(|normal| Collision::WithPaddle(paddle, normal))
+    }
 }
 
 impl Default for Ball {
-    /// Generate a default ball.
+    /// Generate a default ball, at rest in the center of the default arena.
     fn default() -> Self {
         Self {
-            pos: Vec2::default(),
+            pos: ArenaConfig::default().center(),
+            prev_pos: ArenaConfig::default().center(),
             vel: Vec2::default(),
             radius: DEFAULT_RADIUS,
+            max_deflection: DEFAULT_MAX_DEFLECTION,
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_vel_transfer: 0.0,
         }
     }
 }
 
 /// A collision can either take place between a ball and a paddle, or between a ball and an edge.
 pub enum Collision<'a> {
+    /// A collision with an edge of the arena.
     WithEdge(&'a Edges),
-    WithPaddle(&'a Paddle),
+    /// A collision with a paddle, with the unit contact normal pointing from the paddle towards
+    /// the ball.
+    WithPaddle(&'a Paddle, Vec2),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The velocity in pixels per second of an object moving by `(x, y)` pixels per update.
+    fn per_update(x: f32, y: f32) -> Vec2 {
+        &Vec2::new(x, y) * UPDATES_PER_SECOND as f32
+    }
+
+    /// Bounces a ball coming from the right off the front face of the default left paddle, with
+    /// its center the given distance below the center of the paddle, and returns the ball.
+    fn hit_left_paddle(mut ball: Ball, paddle: &Paddle, offset: f32) -> Ball {
+        let arena = ArenaConfig::default();
+        let front = paddle.get_aabb().max.x;
+        ball.pos = Vec2::new(front + ball.radius as f32, paddle.get_pos().y + offset);
+        let normal = Vec2::new(1.0, 0.0);
+        assert_eq!(paddle.contact_region(&normal), PaddleRegion::Front);
+        ball.bounce_after_collision(Collision::WithPaddle(paddle, normal), &arena);
+        ball
+    }
+
+    /// The angle between the horizontal and the velocity of the ball, positive when it goes down.
+    fn angle(ball: &Ball) -> f32 {
+        ball.get_vel().y.atan2(ball.get_vel().x.abs())
+    }
+
+    #[test]
+    fn deflection_depends_on_where_the_ball_hits_the_paddle() {
+        let paddle = Paddle::default_left_paddle(&ArenaConfig::default());
+        let ball = || Ball::new(Vec2::default(), per_update(-4.0, 1.0), 3);
+        let max_deflection = ball().get_max_deflection();
+        let reach = (paddle.get_height() / 2 + 3) as f32;
+
+        // the center sends the ball back horizontally
+        let center = hit_left_paddle(ball(), &paddle, 0.0);
+        assert!(center.get_vel().x > 0.0);
+        assert!(angle(&center).abs() < 1e-5);
+
+        // halfway to the end, the angle is half the maximal one, up or down
+        let lower = hit_left_paddle(ball(), &paddle, reach / 2.0);
+        assert!((angle(&lower) - max_deflection / 2.0).abs() < 1e-5);
+        let upper = hit_left_paddle(ball(), &paddle, -reach / 2.0);
+        assert!((angle(&upper) + max_deflection / 2.0).abs() < 1e-5);
+
+        // the very end, or the ball clipping it, reaches the maximal angle and no further
+        for offset in [reach, reach + 2.0] {
+            let end = hit_left_paddle(ball(), &paddle, offset);
+            assert!((angle(&end) - max_deflection).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn deflection_keeps_the_speed_of_the_ball() {
+        let paddle = Paddle::default_left_paddle(&ArenaConfig::default());
+        let vel = per_update(-4.0, 3.0);
+        for offset in [-20.0, -5.0, 0.0, 7.0, 30.0] {
+            let ball = hit_left_paddle(Ball::new(Vec2::default(), vel.clone(), 3), &paddle, offset);
+            assert!((ball.get_vel().magnitude() - vel.magnitude()).abs() < 1e-3);
+            assert!(ball.get_vel().x > 0.0);
+        }
+    }
+
+    #[test]
+    fn speed_up_stops_at_the_max_speed() {
+        let paddle = Paddle::default_left_paddle(&ArenaConfig::default());
+        let mut ball = Ball::new(Vec2::default(), per_update(-4.0, 0.0), 3);
+        ball.set_speed_up(1.5, per_update(8.0, 0.0).x);
+
+        let mut speeds = Vec::new();
+        for _ in 0..4 {
+            ball = hit_left_paddle(ball, &paddle, 0.0);
+            speeds.push(ball.get_vel().magnitude() / UPDATES_PER_SECOND as f32);
+            ball.vel.x *= -1.0;
+        }
+        for (speed, expected) in speeds.iter().zip([6.0, 8.0, 8.0, 8.0]) {
+            assert!((speed - expected).abs() < 1e-3, "{speeds:?}");
+        }
+
+        // a ball launched faster than the cap keeps its speed but does not speed up
+        let mut fast = Ball::new(Vec2::default(), per_update(-12.0, 0.0), 3);
+        fast.set_speed_up(1.5, per_update(8.0, 0.0).x);
+        let fast = hit_left_paddle(fast, &paddle, 0.0);
+        assert!((fast.get_vel().magnitude() - per_update(12.0, 0.0).x).abs() < 1e-2);
+    }
+
+    #[test]
+    fn moving_paddle_puts_english_on_the_ball() {
+        let arena = ArenaConfig::default();
+        let mut paddle = Paddle::default_left_paddle(&arena);
+        paddle.move_down(&arena);
+        let paddle_vel = paddle.get_vel();
+        assert!(paddle_vel > 0.0);
+        let ball = || Ball::new(Vec2::default(), per_update(-4.0, 0.0), 3);
+
+        // without transfer, the motion of the paddle does not matter
+        let still = hit_left_paddle(ball(), &paddle, 0.0);
+        assert!(still.get_vel().y.abs() < 1e-5);
+
+        let mut spun = ball();
+        spun.set_paddle_vel_transfer(0.1);
+        let spun = hit_left_paddle(spun, &paddle, 0.0);
+        assert!((spun.get_vel().y - 0.1 * paddle_vel).abs() < 1e-3);
+        assert!((spun.get_vel().x - per_update(4.0, 0.0).x).abs() < 1e-3);
+
+        // the english cannot take the ball past its maximal speed
+        let mut capped = ball();
+        capped.set_paddle_vel_transfer(1.0);
+        capped.set_speed_up(1.0, per_update(5.0, 0.0).x);
+        let capped = hit_left_paddle(capped, &paddle, 0.0);
+        assert!(capped.get_vel().y > 0.0);
+        assert!((capped.get_vel().magnitude() - per_update(5.0, 0.0).x).abs() < 1e-2);
+    }
 }
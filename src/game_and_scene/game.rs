@@ -1,43 +1,95 @@
-use rand::rngs::ThreadRng;
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 // use crate::game::paddle;
 
 use super::ball::Ball;
+use super::game_mode::GameMode;
 use super::operation::Operation;
 use super::paddle::Paddle;
 use super::scene::Scene;
-use super::scene::Sides;
+use super::scene::{ScoringEvent, Sides};
+use super::scene_config::{ConfigError, SceneConfig, ScoringConfig, ScoringMode};
+use super::timestep::UPDATES_PER_SECOND;
 
-/// The state of the game is either running or paused.
-enum GameState {
-    Paused,
+/// The state of the game. A match is a sequence of rounds, and each round ends when a ball flies
+/// out of the arena, or when all balls are out in the `per_ball` scoring mode. The match ends when
+/// a side reaches the target score, see `ScoringConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    /// A round is set up and the balls are launched on the next update.
+    Serving,
+    /// A round is being played.
     Running,
+    /// The game is paused, and updates do nothing until it is resumed.
+    Paused,
+    /// The last round is over but the match is not. The next round is started with
+    /// `start_next_round`.
+    RoundOver,
+    /// The match is over, or no match was started yet.
+    MatchOver,
 }
 
 /// The game struct.
 pub struct Game {
-    /// The state of the game. Either running or paused.
+    /// The state of the game.
     state: GameState,
+    /// The state the game goes back to when it is resumed, if it is paused.
+    resume_state: GameState,
     /// The game scene.
     scene: Scene,
     /// The scores of both sides.
     scores: (u32, u32),
-    /// A random number generator instance.
-    rng: ThreadRng,
+    /// The number of the current round of the match, starting at 1. It is 0 before the first
+    /// match is started.
+    round: u32,
+    /// The points both sides scored during the current round.
+    round_scores: (u32, u32),
+    /// The number of updates of the current round so far.
+    round_updates: u64,
+    /// The game mode of the current match. Every round of the match is set up from it.
+    game_mode: GameMode,
+    /// How rounds and matches are scored.
+    scoring: ScoringConfig,
+    /// A random number generator instance. Every random decision in the game is made with it, so
+    /// two games with the same seed and the same sequence of operations play out identically.
+    rng: StdRng,
 }
 
 impl Game {
-    /// Constructs a game.
+    /// Constructs a game. The random number generator is seeded from the operating system, so
+    /// every game is different. Use `with_seed` for a reproducible game.
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Constructs a game whose random number generator is seeded with the given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a game with the given random number generator.
+    fn with_rng(rng: StdRng) -> Self {
         Self {
-            state: GameState::Paused,
+            state: GameState::MatchOver,
+            resume_state: GameState::MatchOver,
             scene: Scene::default(),
             scores: (0, 0),
-            rng: thread_rng(),
+            round: 0,
+            round_scores: (0, 0),
+            round_updates: 0,
+            game_mode: GameMode::Default,
+            scoring: ScoringConfig::default(),
+            rng,
         }
     }
 
+    /// Re-seeds the random number generator of the game. Only the scenes constructed after this
+    /// call are affected.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
     /// Sets the state of the game.
     pub fn set_state(&mut self, state: GameState) {
         self.state = state;
@@ -53,7 +105,7 @@ impl Game {
         self.scores = scores;
     }
 
-    /// Starts the game.
+    /// Gets the scene of the game ready to be served. The balls are launched on the next update.
     pub fn start(&mut self) -> Result<(), String> {
         if self.scene.has_no_balls() {
             return Err(String::from(
@@ -69,28 +121,156 @@ impl Game {
             ));
         }
 
-        self.state = GameState::Running;
+        self.state = GameState::Serving;
         Ok(())
     }
 
-    /// Update the game by updating the scene.
-    pub fn update(&mut self, ops: &mut Vec<Operation>) -> Option<Sides> {
-        if let GameState::Running = self.state {
-            let winner = self.scene.update_scene(ops);
-            if let Some(winner) = winner {
-                self.state = GameState::Paused;
-                if let Sides::Left = winner {
-                    self.scores.0 += 1;
-                } else {
-                    self.scores.1 += 1;
+    /// Update the game by updating the scene. Returns the scoring events of this update, one for
+    /// each ball that flew out of the arena, and adds their points to the scores. If the round
+    /// ended during this update, as decided by the scoring mode and the time limit, the state
+    /// becomes `RoundOver`, and it becomes `MatchOver` as soon as a side won the match. Does
+    /// nothing unless a round is being served or played.
+    pub fn update(&mut self, ops: &mut Vec<Operation>) -> Vec<ScoringEvent> {
+        match self.state {
+            GameState::Serving => self.state = GameState::Running,
+            GameState::Running => {}
+            _ => return Vec::new(),
+        }
+
+        let events = self.scene.update_scene(ops);
+        self.round_updates += 1;
+        let points = self.scoring.points_per_ball;
+        for event in events.iter() {
+            match event.side {
+                Sides::Left => {
+                    self.scores.0 += points;
+                    self.round_scores.0 += points;
+                }
+                Sides::Right => {
+                    self.scores.1 += points;
+                    self.round_scores.1 += points;
                 }
             }
-            winner
+        }
+
+        let mut round_over = match self.scoring.mode {
+            ScoringMode::FirstBall => !events.is_empty(),
+            ScoringMode::PerBall => {
+                // the events are in the order of the balls, so going through them backwards keeps
+                // the indices of the balls still to be removed valid
+                for event in events.iter().rev() {
+                    if self.scoring.reserve_balls {
+                        let ball = self.game_mode.serve_ball(&mut self.rng);
+                        self.scene.replace_ball(event.ball_index, ball);
+                    } else {
+                        self.scene.remove_ball(event.ball_index);
+                    }
+                }
+                self.scene.has_no_balls()
+            }
+        };
+        if let Some(time_limit) = self.scoring.time_limit {
+            let max_updates = (time_limit * UPDATES_PER_SECOND as f32).ceil() as u64;
+            round_over = round_over || self.round_updates >= max_updates;
+        }
+
+        if self.get_match_winner().is_some() {
+            self.state = GameState::MatchOver;
+        } else if round_over {
+            self.state = GameState::RoundOver;
+        }
+        events
+    }
+
+    /// Ends the current round without scoring, for example when a game without a window is stopped
+    /// after a maximum number of frames. The scores of the match are kept, so the next round of
+    /// the match can be started with `start_next_round`. Does nothing unless a round is being
+    /// served or played.
+    pub fn end_round(&mut self) {
+        if let GameState::Serving | GameState::Running = self.state {
+            self.state = GameState::RoundOver;
+        }
+    }
+
+    /// Pauses the game if a round is being served or played.
+    pub fn pause(&mut self) {
+        if let GameState::Serving | GameState::Running = self.state {
+            self.resume_state = self.state;
+            self.state = GameState::Paused;
+        }
+    }
+
+    /// Resumes the game if it is paused.
+    pub fn resume(&mut self) {
+        if let GameState::Paused = self.state {
+            self.state = self.resume_state;
+        }
+    }
+
+    /// Get the number of the current round of the match, starting at 1.
+    pub fn get_round(&self) -> u32 {
+        self.round
+    }
+
+    /// Get the points both sides scored during the current round.
+    pub fn get_round_scores(&self) -> (u32, u32) {
+        self.round_scores
+    }
+
+    /// Get the side that scored the most points during the current round. Returns None if both
+    /// sides scored as many points, for example when the time limit is reached before any ball
+    /// flew out. It is usually read once the round is over.
+    pub fn get_round_winner(&self) -> Option<Sides> {
+        let (left, right) = self.round_scores;
+        match left.cmp(&right) {
+            std::cmp::Ordering::Greater => Some(Sides::Left),
+            std::cmp::Ordering::Less => Some(Sides::Right),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Whether the last round is over, which is also the case when the match is over.
+    pub fn is_round_over(&self) -> bool {
+        matches!(self.state, GameState::RoundOver | GameState::MatchOver)
+    }
+
+    /// Get the state of the game.
+    pub fn get_state(&self) -> GameState {
+        self.state
+    }
+
+    /// Get how rounds and matches are scored.
+    pub fn get_scoring(&self) -> &ScoringConfig {
+        &self.scoring
+    }
+
+    /// Get the winner of the match, that is the side that reached the target score with a lead of
+    /// at least two points if `win_by_two` is set. Returns None if the match is not decided yet.
+    pub fn get_match_winner(&self) -> Option<Sides> {
+        let (left, right) = self.scores;
+        let (leader, leader_score, lead) = if left >= right {
+            (Sides::Left, left, left - right)
+        } else {
+            (Sides::Right, right, right - left)
+        };
+        let min_lead = if self.scoring.win_by_two { 2 } else { 1 };
+        if leader_score >= self.scoring.target_score && lead >= min_lead {
+            Some(leader)
         } else {
             None
         }
     }
 
+    /// Get the scene of the game.
+    pub fn get_scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Get the current scoreboard of the game.
+    pub fn get_scores(&self) -> (u32, u32) {
+        self.scores
+    }
+
     /// Get the number of left paddles.
     pub fn get_left_paddle_count(&self) -> usize {
         self.get_left_paddles().len()
@@ -116,15 +296,67 @@ impl Game {
         self.scene.get_balls()
     }
 
-    /// Starts a default game.
+    /// Starts a match of the default game.
     pub fn start_default_game_with_2_balls(&mut self) {
-        self.scene = Scene::construct_default_scene_with_2_balls(&mut self.rng);
-        self.start().expect("Failed to start the game.");
+        self.game_mode = GameMode::Default;
+        self.scoring = ScoringConfig::default();
+        self.reset();
+    }
+
+    /// Starts a match of a custom game as described by a scene configuration. Returns an error if
+    /// the configuration is invalid.
+    pub fn start_custom_game(&mut self, config: &SceneConfig) -> Result<(), ConfigError> {
+        config.validate()?;
+        self.game_mode = GameMode::Custom(Box::new(config.clone()));
+        self.scoring = config.scoring.clone();
+        self.reset();
+        Ok(())
+    }
+
+    /// Starts a match of the given game mode.
+    pub fn start_game(&mut self, game_mode: &GameMode) -> Result<(), ConfigError> {
+        match game_mode {
+            GameMode::Default => {
+                self.start_default_game_with_2_balls();
+                Ok(())
+            }
+            GameMode::Custom(config) => self.start_custom_game(config),
+        }
     }
 
-    /// Resets the game. Unimplemented.
+    /// Starts the next round of the current match, keeping the scores. If the last round is not
+    /// over, for example because the match is over or no match was started yet, starts a new
+    /// match of the given game mode instead.
+    pub fn start_next_round(&mut self, game_mode: &GameMode) -> Result<(), ConfigError> {
+        match self.state {
+            GameState::RoundOver => {
+                self.set_up_round();
+                Ok(())
+            }
+            _ => self.start_game(game_mode),
+        }
+    }
+
+    /// Resets the current match. The scores are cleared and a new scene of the game mode of the
+    /// match is constructed with freshly drawn balls, ready to be served.
     pub fn reset(&mut self) {
-        unimplemented!("reset unimplemented!");
+        self.scores = (0, 0);
+        self.round = 0;
+        self.set_up_round();
+    }
+
+    /// Constructs a new scene of the game mode of the current match and gets it ready to be
+    /// served as the next round.
+    fn set_up_round(&mut self) {
+        self.round += 1;
+        self.round_scores = (0, 0);
+        self.round_updates = 0;
+        self.scene = match &self.game_mode {
+            GameMode::Default => Scene::construct_default_scene_with_2_balls(&mut self.rng),
+            GameMode::Custom(config) => Scene::construct_scene_from_config(config, &mut self.rng)
+                .expect("The scene configuration was validated when the match started."),
+        };
+        self.start().expect("Failed to start the game.");
     }
 
     // pub fn randomize(
@@ -139,3 +371,195 @@ impl Game {
     //     todo!("Implement randomize method for game.");
     // }
 }
+
+impl Default for Game {
+    /// Generate a default game.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::operation::OperationTypes;
+    use super::*;
+    use crate::math_utils::vec2::Vec2;
+
+    /// Everything a game with the given seed looks like after being fed the same sequence of
+    /// operations for a number of updates: the positions of the balls and the paddles, and the
+    /// scores.
+    fn play_seeded_game(seed: u64) -> (Vec<Vec2>, Vec<Vec2>, (u32, u32)) {
+        let mut game = Game::with_seed(seed);
+        game.start_game(&GameMode::Default).unwrap();
+
+        for update in 0..2000 {
+            let op = if update % 50 < 25 {
+                OperationTypes::Up
+            } else {
+                OperationTypes::Down
+            };
+            let mut ops = vec![
+                Operation::new(op, Sides::Left, update % 2),
+                Operation::new(op, Sides::Right, (update + 1) % 2),
+            ];
+            game.update(&mut ops);
+            if game.is_round_over() {
+                game.start_next_round(&GameMode::Default).unwrap();
+            }
+        }
+
+        let balls = game
+            .get_balls()
+            .iter()
+            .map(|ball| ball.get_pos().clone())
+            .collect();
+        let paddles = game
+            .get_left_paddles()
+            .iter()
+            .chain(game.get_right_paddles())
+            .map(|paddle| paddle.get_pos().clone())
+            .collect();
+        (balls, paddles, game.get_scores())
+    }
+
+    #[test]
+    fn games_with_the_same_seed_play_identically() {
+        let first = play_seeded_game(42);
+
+        assert_eq!(first, play_seeded_game(42));
+        assert_ne!(first, play_seeded_game(43));
+    }
+
+    #[test]
+    fn one_paddle_games_ignore_operations_on_a_second_paddle() {
+        let config = SceneConfig::from_toml_str("[left_paddles]\ncount = 1\n").unwrap();
+        let mut game = Game::with_seed(0);
+        game.start_game(&GameMode::Custom(Box::new(config)))
+            .unwrap();
+
+        game.update(&mut vec![
+            Operation::new(OperationTypes::Up, Sides::Left, 1),
+            Operation::new(OperationTypes::Down, Sides::Left, 1),
+        ]);
+
+        assert_eq!(game.get_state(), GameState::Running);
+    }
+
+    /// Updates the game without moving any paddle until the current round is over.
+    fn play_out_round(game: &mut Game) {
+        while !game.is_round_over() {
+            game.update(&mut Vec::new());
+        }
+    }
+
+    #[test]
+    fn every_ball_out_is_worth_the_points_per_ball() {
+        let config = SceneConfig::from_toml_str(
+            "[balls]\ncount = 3\n\n[scoring]\npoints_per_ball = 2\nmode = \"per_ball\"\n",
+        )
+        .unwrap();
+        let mut game = Game::with_seed(1);
+        game.start_game(&GameMode::Custom(Box::new(config)))
+            .unwrap();
+
+        play_out_round(&mut game);
+
+        let (left, right) = game.get_scores();
+        assert_eq!((left % 2, right % 2), (0, 0));
+        assert_eq!(left + right, 3 * 2);
+        assert_eq!(game.get_round_scores(), (left, right));
+    }
+
+    #[test]
+    fn match_goes_on_at_deuce() {
+        let mut game = Game::with_seed(0);
+        game.start_game(&GameMode::Default).unwrap();
+
+        for (scores, winner) in [
+            ((10, 9), None),
+            ((11, 10), None),
+            ((10, 11), None),
+            ((11, 11), None),
+            ((11, 9), Some(Sides::Left)),
+            ((12, 10), Some(Sides::Left)),
+            ((13, 15), Some(Sides::Right)),
+        ] {
+            game.set_scores(scores);
+            assert_eq!(game.get_match_winner(), winner, "{scores:?}");
+        }
+    }
+
+    #[test]
+    fn match_is_won_by_a_single_point_without_win_by_two() {
+        let config =
+            SceneConfig::from_toml_str("[scoring]\ntarget_score = 3\nwin_by_two = false\n")
+                .unwrap();
+        let mut game = Game::with_seed(0);
+        game.start_game(&GameMode::Custom(Box::new(config)))
+            .unwrap();
+
+        for (scores, winner) in [
+            ((2, 2), None),
+            ((3, 2), Some(Sides::Left)),
+            ((2, 3), Some(Sides::Right)),
+        ] {
+            game.set_scores(scores);
+            assert_eq!(game.get_match_winner(), winner, "{scores:?}");
+        }
+    }
+
+    #[test]
+    fn round_at_deuce_only_ends_the_match_with_a_lead_of_two() {
+        let mut game = Game::with_seed(3);
+        game.start_game(&GameMode::Default).unwrap();
+        game.set_scores((11, 10));
+
+        play_out_round(&mut game);
+
+        let (left, right) = game.get_scores();
+        if left >= right + 2 {
+            assert_eq!(game.get_state(), GameState::MatchOver);
+            assert_eq!(game.get_match_winner(), Some(Sides::Left));
+        } else {
+            assert_eq!(game.get_state(), GameState::RoundOver);
+            assert_eq!(game.get_match_winner(), None);
+        }
+    }
+
+    #[test]
+    fn match_over_starts_once_a_side_wins() {
+        let mode = GameMode::Custom(Box::new(
+            SceneConfig::from_toml_str("[scoring]\ntarget_score = 2\n").unwrap(),
+        ));
+        let mut game = Game::with_seed(5);
+        game.start_game(&mode).unwrap();
+
+        for _ in 0..100 {
+            play_out_round(&mut game);
+            if game.get_state() == GameState::MatchOver {
+                break;
+            }
+            assert_eq!(game.get_match_winner(), None);
+            game.start_next_round(&mode).unwrap();
+        }
+
+        assert_eq!(game.get_state(), GameState::MatchOver);
+        let winner = game
+            .get_match_winner()
+            .expect("the match should have a winner");
+        let (left, right) = game.get_scores();
+        let (winner_score, loser_score) = match winner {
+            Sides::Left => (left, right),
+            Sides::Right => (right, left),
+        };
+        assert!(winner_score >= 2 && winner_score >= loser_score + 2);
+        // a finished match is frozen until the next one is started
+        assert!(game.update(&mut Vec::new()).is_empty());
+        assert_eq!(game.get_scores(), (left, right));
+
+        game.start_next_round(&mode).unwrap();
+        assert_eq!(game.get_state(), GameState::Serving);
+        assert_eq!(game.get_scores(), (0, 0));
+        assert_eq!(game.get_round(), 1);
+    }
+}
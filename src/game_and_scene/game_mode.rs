@@ -1,7 +1,13 @@
+use rand::Rng;
+
+use super::arena::ArenaConfig;
+use super::ball::Ball;
+use super::scene_config::SceneConfig;
+
 // a default game is a game with 2 paddles on each side and 2 balls
-// a custom game can be customized, but it is not yet implemented
+// a custom game is described by a scene configuration
 /// There are 2 game modes in total, one is default and the other is custom.
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub enum GameMode {
     /// The default game is defined as a game such that:
     /// - there are 2 paddles on each side, so 4 paddles in total;
@@ -9,6 +15,34 @@ pub enum GameMode {
     /// - the paddles and balls have default sizes and default initial positions;
     /// - the balls are launched from the center of the screen.
     Default,
-    /// Custom games allow you to modify any of the properties mentioned above.
-    Custom,
+    /// Custom games allow you to modify any of the properties mentioned above, as described by
+    /// the scene configuration.
+    Custom(Box<SceneConfig>),
+}
+
+impl GameMode {
+    /// Get the dimensions of the arena the games of this mode are played in.
+    pub fn get_arena(&self) -> ArenaConfig {
+        match self {
+            GameMode::Default => ArenaConfig::default(),
+            GameMode::Custom(config) => config.screen,
+        }
+    }
+
+    /// Get the number of paddles on the left and on the right side of the games of this mode.
+    pub fn get_paddle_counts(&self) -> (usize, usize) {
+        match self {
+            GameMode::Default => (2, 2),
+            GameMode::Custom(config) => (config.left_paddles.count, config.right_paddles.count),
+        }
+    }
+
+    /// Serves a new ball of this mode from the center of the arena, for example to replace a ball
+    /// that flew out.
+    pub fn serve_ball<R: Rng + ?Sized>(&self, rng: &mut R) -> Ball {
+        match self {
+            GameMode::Default => Ball::random_centered_ball(&ArenaConfig::default(), rng),
+            GameMode::Custom(config) => config.balls.serve(&config.screen, rng),
+        }
+    }
 }
@@ -1,4 +1,6 @@
 // scene-related modules
+/// The ArenaConfig struct.
+pub mod arena;
 /// The Ball struct.
 pub mod ball;
 /// The Paddle struct.
@@ -13,5 +15,14 @@ pub mod operation;
 /// The GameMode enum.
 pub mod game_mode;
 
+/// The SceneConfig struct, describing custom games.
+pub mod scene_config;
+
 /// The Game struct.
 pub mod game;
+
+/// The Replay struct.
+pub mod replay;
+
+/// The fixed time step of the simulation, and the FixedTimestep accumulator.
+pub mod timestep;
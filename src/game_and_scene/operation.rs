@@ -1,5 +1,6 @@
 use super::scene::Sides;
 
+#[cfg(feature = "sdl")]
 use sdl2::keyboard::Keycode;
 
 /// There are 3 types of operations on each paddle in total, that are up, down and stay.
@@ -38,6 +39,7 @@ impl Operation {
     /// Converts a keycode into an operation. This implementation only supports default games. This
     /// is because if there are more than 2 paddles, there is no obvious way of mapping keyboard
     /// inputs to operations.
+    #[cfg(feature = "sdl")]
     pub fn from_key_code(keycode: Keycode) -> Option<Self> {
         match keycode {
             Keycode::W => Some(Self::new(OperationTypes::Up, Sides::Left, 0)),
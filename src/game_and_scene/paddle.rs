@@ -1,65 +1,112 @@
-use crate::math_utils::vec2::Vec2;
+use crate::math_utils::{collision::Aabb, vec2::Vec2};
 
+use super::arena::{ArenaConfig, DEFAULT_ARENA_HEIGHT};
 use super::scene::Sides;
-use super::scene::PADDLE_MARGIN;
-use super::scene::SCREEN_HEIGHT;
-use super::scene::SCREEN_WIDTH;
+use super::timestep::TIME_STEP;
 
 use super::ball::DEFAULT_BALL_SPEED;
 
 /// The default paddle height.
-pub const DEFAULT_PADDLE_HEIGHT: u32 = SCREEN_HEIGHT / 10;
+pub const DEFAULT_PADDLE_HEIGHT: u32 = DEFAULT_ARENA_HEIGHT / 10;
 /// The default paddle width.
 pub const DEFAULT_PADDLE_WIDTH: u32 = DEFAULT_PADDLE_HEIGHT / 8;
-/// The default paddle speed. The unit is pixels per frame.
+/// The default paddle speed, used by paddles whose speed is not set. The unit is pixels per
+/// second.
 pub const DEFAULT_PADDLE_SPEED: f32 = DEFAULT_BALL_SPEED * 5.0;
 
-/// The default x position of the center of the left paddles.
-pub const DEFAULT_LEFT_CENTER_X: f32 = (PADDLE_MARGIN + DEFAULT_PADDLE_WIDTH / 2) as f32;
-/// The default x position of the center of the right paddles.
-pub const DEFAULT_RIGHT_CENTER_X: f32 =
-    (SCREEN_WIDTH - PADDLE_MARGIN - DEFAULT_PADDLE_WIDTH / 2) as f32;
+/// The region of a paddle a ball touches, which decides how the ball bounces off it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddleRegion {
+    /// The face towards the center of the arena, that players hit the ball with.
+    Front,
+    /// The face towards the edge of the arena behind the paddle.
+    Back,
+    /// The top face.
+    Top,
+    /// The bottom face.
+    Bottom,
+    /// One of the 4 corners.
+    Corner,
+}
 
 /// The paddle struct.
 pub struct Paddle {
     /// The position of the center of the paddle.
     pos: Vec2,
+    /// The position of the center of the paddle before the last update, to interpolate between
+    /// the last two updates when rendering.
+    prev_pos: Vec2,
     /// The width of the paddle.
     width: u32,
     /// The height of the paddle.
     height: u32,
     /// The side the paddle is on.
     side: Sides,
+    /// The speed the paddle moves at while it receives up or down operations. If the paddle
+    /// accelerates, this is its maximal speed instead. The unit is pixels per second.
+    speed: f32,
+    /// How much the speed of the paddle grows for each up or down operation, until it reaches
+    /// `speed`. If there is none, the paddle moves at full speed right away. The unit is pixels
+    /// per second per second.
+    acceleration: Option<f32>,
+    /// The vertical velocity of the paddle during the last update. It is negative when the paddle
+    /// moved up and 0 when the paddle did not move. The unit is pixels per second.
+    vel: f32,
+    /// The top and the bottom of the vertical band the paddle has to stay in, if any.
+    lane: Option<(f32, f32)>,
 }
 
 impl Paddle {
     /// The constructor of a paddle.
     pub fn new(pos: Vec2, width: u32, height: u32, side: Sides) -> Self {
         Self {
+            prev_pos: pos.clone(),
             pos,
             width,
             height,
             side,
+            speed: DEFAULT_PADDLE_SPEED,
+            acceleration: None,
+            vel: 0.0,
+            lane: None,
         }
     }
 
-    /// Generates a default left paddle.
-    pub fn default_left_paddle() -> Self {
+    /// Generates a default left paddle in the given arena.
+    pub fn default_left_paddle(arena: &ArenaConfig) -> Self {
+        let pos = Vec2::new(
+            arena.paddle_center_x(Sides::Left, DEFAULT_PADDLE_WIDTH),
+            arena.center().y,
+        );
         Self {
-            pos: Vec2::new(DEFAULT_LEFT_CENTER_X, (SCREEN_HEIGHT / 2) as f32),
-            width: PADDLE_MARGIN,
+            prev_pos: pos.clone(),
+            pos,
+            width: DEFAULT_PADDLE_WIDTH,
             height: DEFAULT_PADDLE_HEIGHT,
             side: Sides::Left,
+            speed: DEFAULT_PADDLE_SPEED,
+            acceleration: None,
+            vel: 0.0,
+            lane: None,
         }
     }
 
-    /// Generates a default left paddle.
-    pub fn default_right_paddle() -> Self {
+    /// Generates a default right paddle in the given arena.
+    pub fn default_right_paddle(arena: &ArenaConfig) -> Self {
+        let pos = Vec2::new(
+            arena.paddle_center_x(Sides::Right, DEFAULT_PADDLE_WIDTH),
+            arena.center().y,
+        );
         Self {
-            pos: Vec2::new(DEFAULT_RIGHT_CENTER_X, (SCREEN_HEIGHT / 2) as f32),
-            width: PADDLE_MARGIN,
+            prev_pos: pos.clone(),
+            pos,
+            width: DEFAULT_PADDLE_WIDTH,
             height: DEFAULT_PADDLE_HEIGHT,
             side: Sides::Right,
+            speed: DEFAULT_PADDLE_SPEED,
+            acceleration: None,
+            vel: 0.0,
+            lane: None,
         }
     }
 
@@ -69,6 +116,52 @@ impl Paddle {
         &self.pos
     }
 
+    /// Moves the paddle vertically so that its center is at the given y position, for example to
+    /// spread the paddles of a side over the height of the arena.
+    pub fn set_y(&mut self, y: f32) {
+        self.pos.y = y;
+        self.prev_pos.y = y;
+    }
+
+    /// Get the position of the center of the paddle at the given fraction of the way from its
+    /// position before the last update to its current position.
+    pub fn get_interpolated_pos(&self, alpha: f32) -> Vec2 {
+        &self.prev_pos + &(&(&self.pos - &self.prev_pos) * alpha)
+    }
+
+    /// Remembers the current position as the position before the update that is about to run.
+    pub fn save_pos(&mut self) {
+        self.prev_pos = self.pos.clone();
+    }
+
+    /// Get how far the paddle moved since the start of the current update, or during the last
+    /// update once it is over.
+    pub fn get_motion(&self) -> Vec2 {
+        &self.pos - &self.prev_pos
+    }
+
+    /// Get the box covered by the paddle.
+    pub fn get_aabb(&self) -> Aabb {
+        Aabb::from_center(&self.pos, self.width as f32, self.height as f32)
+    }
+
+    /// The region of the paddle with the given contact normal, which points from the paddle
+    /// towards the ball. Axis-aligned normals belong to faces, and any other normal to a corner.
+    pub fn contact_region(&self, normal: &Vec2) -> PaddleRegion {
+        let towards_center = if self.is_left() { 1.0 } else { -1.0 };
+        if normal.y == 0.0 && normal.x == towards_center {
+            PaddleRegion::Front
+        } else if normal.y == 0.0 && normal.x == -towards_center {
+            PaddleRegion::Back
+        } else if normal.x == 0.0 && normal.y < 0.0 {
+            PaddleRegion::Top
+        } else if normal.x == 0.0 && normal.y > 0.0 {
+            PaddleRegion::Bottom
+        } else {
+            PaddleRegion::Corner
+        }
+    }
+
     /// Get the width of the paddle.
     pub fn get_width(&self) -> u32 {
         self.width
@@ -79,6 +172,43 @@ impl Paddle {
         self.height
     }
 
+    /// Get the speed of the paddle.
+    pub fn get_speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the speed of the paddle. If the paddle accelerates, this is its maximal speed.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Get the acceleration of the paddle, if it accelerates.
+    pub fn get_acceleration(&self) -> Option<f32> {
+        self.acceleration
+    }
+
+    /// Sets the acceleration of the paddle. With None, the paddle moves at full speed right away.
+    pub fn set_acceleration(&mut self, acceleration: Option<f32>) {
+        self.acceleration = acceleration;
+    }
+
+    /// Get the vertical velocity of the paddle during the last update. It is negative when the
+    /// paddle moved up.
+    pub fn get_vel(&self) -> f32 {
+        self.vel
+    }
+
+    /// Get the top and the bottom of the vertical band the paddle has to stay in, if any.
+    pub fn get_lane(&self) -> Option<(f32, f32)> {
+        self.lane
+    }
+
+    /// Set the top and the bottom of the vertical band the paddle has to stay in. With None, the
+    /// paddle can move over the whole height of the arena.
+    pub fn set_lane(&mut self, lane: Option<(f32, f32)>) {
+        self.lane = lane;
+    }
+
     /// Whether the paddle is a left paddle.
     pub fn is_left(&self) -> bool {
         matches!(self.side, Sides::Left)
@@ -89,19 +219,75 @@ impl Paddle {
         matches!(self.side, Sides::Right)
     }
 
-    /// The method called when an up operation is received.
+    /// Updates the velocity of the paddle for a move in the given direction, which is -1 for up
+    /// and 1 for down. Without acceleration, the paddle moves at full speed. With acceleration, it
+    /// speeds up by the acceleration until it reaches its maximal speed, and starts from rest
+    /// when it changes its direction.
+    fn accelerate(&mut self, direction: f32) {
+        self.vel = match self.acceleration {
+            None => direction * self.speed,
+            Some(acceleration) => {
+                let vel = if self.vel * direction < 0.0 {
+                    0.0
+                } else {
+                    self.vel
+                };
+                (vel + direction * acceleration * TIME_STEP).clamp(-self.speed, self.speed)
+            }
+        };
+    }
+
+    /// The method called when an up operation is received. The whole paddle stays below the top
+    /// edge of the arena and in its lane: a move that would overshoot stops the paddle right at
+    /// the edge.
     pub fn move_up(&mut self) {
-        let final_y = self.pos.y - DEFAULT_PADDLE_SPEED;
-        if final_y >= 0.0 {
-            self.pos.y -= DEFAULT_PADDLE_SPEED;
+        self.accelerate(-1.0);
+        self.pos.y += self.vel * TIME_STEP;
+        self.block(self.height as f32 / 2.0, f32::INFINITY);
+        self.keep_in_lane();
+    }
+
+    /// The method called when a down operation is received. The whole paddle stays above the
+    /// bottom edge of the given arena and in its lane: a move that would overshoot stops the
+    /// paddle right at the edge.
+    pub fn move_down(&mut self, arena: &ArenaConfig) {
+        self.accelerate(1.0);
+        self.pos.y += self.vel * TIME_STEP;
+        self.block(
+            f32::NEG_INFINITY,
+            arena.height_f32() - self.height as f32 / 2.0,
+        );
+        self.keep_in_lane();
+    }
+
+    /// Stops the paddle at the end of its lane if its last move took it out of the lane.
+    fn keep_in_lane(&mut self) {
+        if let Some((top, bottom)) = self.lane {
+            let half_height = self.height as f32 / 2.0;
+            self.block(top + half_height, bottom - half_height);
         }
     }
 
-    /// The method called when a down operation is received.
-    pub fn move_down(&mut self) {
-        let final_y = self.pos.y + DEFAULT_PADDLE_SPEED;
-        if final_y <= SCREEN_HEIGHT as f32 {
-            self.pos.y += DEFAULT_PADDLE_SPEED;
+    /// Takes back the part of the last move of the paddle that took its center above `min_y` or
+    /// below `max_y`, for example because it ran into another paddle. The velocity becomes the
+    /// velocity the paddle actually moved at. A paddle that was already out of the range before its
+    /// move is not pulled back into it, but cannot move further out.
+    pub fn block(&mut self, min_y: f32, max_y: f32) {
+        let previous_y = self.pos.y - self.vel * TIME_STEP;
+        let y = if self.vel < 0.0 {
+            self.pos.y.max(min_y.min(previous_y))
+        } else {
+            self.pos.y.min(max_y.max(previous_y))
+        };
+        if y != self.pos.y {
+            self.pos.y = y;
+            self.vel = (y - previous_y) / TIME_STEP;
         }
     }
+
+    /// The method called when the paddle receives no up or down operation during an update. The
+    /// paddle stops, so an accelerating paddle starts from rest on its next move.
+    pub fn stay(&mut self) {
+        self.vel = 0.0;
+    }
 }
@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::Path;
+
+use super::game_mode::GameMode;
+use super::operation::{Operation, OperationTypes};
+use super::scene::Sides;
+
+/// The first line of every replay file.
+pub const REPLAY_HEADER: &str = "# rust-pong replay";
+
+/// A recording of a sequence of games. Since a game is deterministic given its seed and its
+/// operations, a replay only stores the seed of the game and the operations of every frame, grouped
+/// by round. Replaying it on a game constructed with `Game::with_seed` reproduces every frame.
+///
+/// Replays are saved as text files. After the header, the seed is given by a `seed <seed>` line,
+/// each round starts with a `round` line, and each frame is a `frame` line followed by its
+/// operations. An operation is written as its side (`L` or `R`), the index of the paddle and its
+/// type (`U`, `D` or `S`), so `frame L0U R1D` moves the first left paddle up and the second right
+/// paddle down.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// The seed of the recorded game.
+    seed: u64,
+    /// The operations of every frame of every round.
+    rounds: Vec<Vec<Vec<Operation>>>,
+}
+
+impl Replay {
+    /// Constructs an empty replay of a game with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rounds: Vec::new(),
+        }
+    }
+
+    /// Get the seed of the recorded game.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the operations of every frame of every round.
+    pub fn get_rounds(&self) -> &Vec<Vec<Vec<Operation>>> {
+        &self.rounds
+    }
+
+    /// Starts recording a new round.
+    pub fn start_round(&mut self) {
+        self.rounds.push(Vec::new());
+    }
+
+    /// Records the operations of a frame in the current round. Starts a round if there is none.
+    pub fn push_frame(&mut self, ops: &[Operation]) {
+        if self.rounds.is_empty() {
+            self.start_round();
+        }
+        if let Some(round) = self.rounds.last_mut() {
+            round.push(ops.to_vec());
+        }
+    }
+
+    /// Converts the replay into the text of a replay file.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{REPLAY_HEADER}\nseed {}\n", self.seed);
+        for round in self.rounds.iter() {
+            text.push_str("round\n");
+            for frame in round.iter() {
+                text.push_str("frame");
+                for op in frame.iter() {
+                    text.push(' ');
+                    text.push_str(&encode_operation(op));
+                }
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Parses the text of a replay file.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()));
+
+        match lines.next() {
+            Some((_, REPLAY_HEADER)) => {}
+            _ => return Err(String::from("Error: this is not a replay file.")),
+        }
+
+        let mut replay = match lines.next() {
+            Some((line_number, line)) => {
+                let seed = line
+                    .strip_prefix("seed ")
+                    .and_then(|seed| seed.trim().parse().ok())
+                    .ok_or_else(|| format!("Error: line {line_number}: expected `seed <seed>`."))?;
+                Self::new(seed)
+            }
+            None => return Err(String::from("Error: the replay file has no seed.")),
+        };
+
+        for (line_number, line) in lines {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => {}
+                Some("round") => replay.start_round(),
+                Some("frame") => {
+                    if replay.rounds.is_empty() {
+                        return Err(format!(
+                            "Error: line {line_number}: a frame must be inside a round."
+                        ));
+                    }
+                    let ops = words
+                        .map(|word| {
+                            decode_operation(word).ok_or_else(|| {
+                                format!("Error: line {line_number}: invalid operation `{word}`.")
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    replay.push_frame(&ops);
+                }
+                Some(word) => {
+                    return Err(format!(
+                    "Error: line {line_number}: unexpected `{word}`, expected `round` or `frame`."
+                ))
+                }
+            }
+        }
+
+        Ok(replay)
+    }
+
+    /// Checks that every operation of the replay acts on a paddle that exists in the given game
+    /// mode. Replay files do not store their game mode, so a replay has to be checked against the
+    /// game mode it is played back with before it is run.
+    pub fn validate(&self, game_mode: &GameMode) -> Result<(), String> {
+        let (left_count, right_count) = game_mode.get_paddle_counts();
+        for (round_number, round) in (1..).zip(self.rounds.iter()) {
+            for (frame_number, frame) in (1..).zip(round.iter()) {
+                for op in frame.iter() {
+                    let (side, count) = match op.side {
+                        Sides::Left => ("left", left_count),
+                        Sides::Right => ("right", right_count),
+                    };
+                    if op.index >= count {
+                        return Err(format!(
+                            "Error: round {round_number}, frame {frame_number}: invalid operation \
+                             `{}`, there are only {count} {side} paddles.",
+                            encode_operation(op)
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the replay into a file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_text())
+            .map_err(|e| format!("Error: failed to write {}: {e}", path.display()))
+    }
+
+    /// Loads a replay from a file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error: failed to read {}: {e}", path.display()))?;
+        Self::from_text(&text)
+    }
+}
+
+/// Encodes an operation as a word of a replay file, for example `L0U`.
+fn encode_operation(op: &Operation) -> String {
+    let side = match op.side {
+        Sides::Left => 'L',
+        Sides::Right => 'R',
+    };
+    let op_type = match op.op_type {
+        OperationTypes::Up => 'U',
+        OperationTypes::Down => 'D',
+        OperationTypes::Stay => 'S',
+    };
+    format!("{side}{}{op_type}", op.index)
+}
+
+/// Decodes a word of a replay file into an operation.
+fn decode_operation(word: &str) -> Option<Operation> {
+    let side = match word.chars().next()? {
+        'L' => Sides::Left,
+        'R' => Sides::Right,
+        _ => return None,
+    };
+    let op_type = match word.chars().last()? {
+        'U' => OperationTypes::Up,
+        'D' => OperationTypes::Down,
+        'S' => OperationTypes::Stay,
+        _ => return None,
+    };
+    let index = word.get(1..word.len() - 1)?.parse().ok()?;
+    Some(Operation::new(op_type, side, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::Game;
+    use super::*;
+    use crate::agent::agents::TrackingAgent;
+    use crate::agent::headless::{run_games, run_replay};
+
+    #[test]
+    fn operations_on_missing_paddles_are_rejected() {
+        let text = format!("{REPLAY_HEADER}\nseed 3\nround\nframe L1U R0D\nframe\nframe L9U\n");
+        let replay = Replay::from_text(&text).unwrap();
+
+        let error = replay.validate(&GameMode::Default).unwrap_err();
+
+        assert!(error.contains("round 1, frame 3"), "{error}");
+        assert!(error.contains("`L9U`"), "{error}");
+        let mut game = Game::with_seed(replay.get_seed());
+        assert!(run_replay(&mut game, &GameMode::Default, &replay).is_err());
+    }
+
+    #[test]
+    fn saved_replays_play_back_the_same_rounds() {
+        let mut game = Game::with_seed(11);
+        let mut recorded = Replay::new(11);
+        let summary = run_games(
+            &mut game,
+            &GameMode::Default,
+            5,
+            Some(2000),
+            &mut TrackingAgent,
+            &mut TrackingAgent,
+            Some(&mut recorded),
+        )
+        .unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("rust_pong_replay_{}.txt", std::process::id()));
+        recorded.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.get_seed(), 11);
+        assert_eq!(loaded.to_text(), recorded.to_text());
+        let mut replayed = Game::with_seed(loaded.get_seed());
+        let replayed_summary = run_replay(&mut replayed, &GameMode::Default, &loaded).unwrap();
+        assert_eq!(
+            (
+                replayed_summary.left_wins,
+                replayed_summary.right_wins,
+                replayed_summary.draws,
+                replayed_summary.frames
+            ),
+            (
+                summary.left_wins,
+                summary.right_wins,
+                summary.draws,
+                summary.frames
+            )
+        );
+        assert_eq!(replayed.get_scores(), game.get_scores());
+        assert_eq!(replayed.get_round(), game.get_round());
+    }
+}
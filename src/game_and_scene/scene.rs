@@ -1,21 +1,21 @@
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
+use super::arena::ArenaConfig;
 use super::ball::Collision;
 use super::operation::{Operation, OperationTypes};
+use super::scene_config::{BallCollisionConfig, ConfigError, PaddleConfig, SceneConfig};
 use super::{ball::Ball, paddle::Paddle};
+use crate::math_utils::{
+    collision::{overlapping_pairs, Aabb},
+    vec2::Vec2,
+};
 
-use super::paddle::DEFAULT_PADDLE_WIDTH;
-
-/// The default screen width.
-pub const SCREEN_WIDTH: u32 = 800;
-/// The default screen height.
-pub const SCREEN_HEIGHT: u32 = 600;
-
-/// The default margin on the left and the right of the screen. This is the distance between the
-/// left/right edge of the left/right paddles and the left/right edges.
-pub const PADDLE_MARGIN: u32 = DEFAULT_PADDLE_WIDTH;
+/// The maximal number of bounces of a ball during a single update. A ball that bounces more often
+/// stops where its last bounce took place for the rest of the update.
+pub const MAX_BOUNCES_PER_UPDATE: u32 = 8;
 
 /// The 4 edges of the scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Edges {
     /// The top edge.
     Top,
@@ -28,7 +28,7 @@ pub enum Edges {
 }
 
 /// There are 2 sides in the game, that are left and right.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Sides {
     /// The left side of the scene.
     Left,
@@ -36,19 +36,78 @@ pub enum Sides {
     Right,
 }
 
+/// Something a ball can bounce off during an update of the scene.
+enum Contact {
+    /// The top or the bottom edge.
+    Edge(Edges),
+    /// The paddle of the given index on the given side, with the contact normal.
+    Paddle(Sides, usize, Vec2),
+}
+
+/// A ball hitting a paddle during an update of the scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddleHit {
+    /// The side of the paddle that was hit.
+    pub side: Sides,
+    /// The index of the paddle that was hit among the paddles on its side.
+    pub paddle_index: usize,
+    /// The index of the ball that hit the paddle.
+    pub ball_index: usize,
+}
+
+/// A ball flying out of the arena during an update of the scene, which scores a point for the
+/// side opposite to the edge it left through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringEvent {
+    /// The side that scores.
+    pub side: Sides,
+    /// The index of the ball that flew out, among the balls of the scene during the update.
+    pub ball_index: usize,
+}
+
 /// The scene struct. It contains vectors of left and right paddles and balls. Edges are not
-/// contained since there will always be 4 edges.
+/// contained since there will always be 4 edges, which are given by the arena of the scene.
 #[derive(Default)]
 pub struct Scene {
+    /// The dimensions of the arena.
+    arena: ArenaConfig,
     /// The vector containing all left paddles.
     left_paddles: Vec<Paddle>,
     /// The vector containing all right paddles.
     right_paddles: Vec<Paddle>,
     /// The vector containing all balls.
     balls: Vec<Ball>,
+    /// All paddle hits that happened during the last update.
+    last_hits: Vec<PaddleHit>,
+    /// How the balls bounce off each other.
+    ball_collisions: BallCollisionConfig,
 }
 
 impl Scene {
+    /// Constructs an empty scene in the given arena.
+    pub fn with_arena(arena: ArenaConfig) -> Self {
+        Self {
+            arena,
+            ..Self::default()
+        }
+    }
+
+    /// Get the dimensions of the arena.
+    pub fn get_arena(&self) -> &ArenaConfig {
+        &self.arena
+    }
+
+    /// Remove the ball of the given index from the scene. The balls after it move down by one
+    /// index.
+    pub fn remove_ball(&mut self, index: usize) -> Ball {
+        self.balls.remove(index)
+    }
+
+    /// Replace the ball of the given index, for example to serve a new ball after it flew out.
+    pub fn replace_ball(&mut self, index: usize, ball: Ball) {
+        self.balls[index] = ball;
+    }
+
     /// Add balls into the scene.
     pub fn add_balls(&mut self, balls: Vec<Ball>) {
         self.balls.extend(balls);
@@ -133,82 +192,874 @@ impl Scene {
         &self.balls
     }
 
-    /// Construct a default scene. A default scene is defined as the scene in a default game, and
-    /// a default game is defined in `main.rs`.
-    pub fn construct_default_scene_with_2_balls(rng: &mut ThreadRng) -> Self {
+    /// Get how the balls bounce off each other.
+    pub fn get_ball_collisions(&self) -> &BallCollisionConfig {
+        &self.ball_collisions
+    }
+
+    /// Set how the balls bounce off each other, for example to let them pass through one another.
+    pub fn set_ball_collisions(&mut self, ball_collisions: BallCollisionConfig) {
+        self.ball_collisions = ball_collisions;
+    }
+
+    /// Get all paddle hits that happened during the last update of the scene.
+    pub fn get_last_hits(&self) -> &Vec<PaddleHit> {
+        &self.last_hits
+    }
+
+    /// Construct a default scene. A default scene is defined as the scene in a default game, that
+    /// is a game of `GameMode::Default`, and it is built here.
+    pub fn construct_default_scene_with_2_balls<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let arena = ArenaConfig::default();
         Self {
-            left_paddles: vec![Paddle::default_left_paddle(), Paddle::default_left_paddle()],
-            right_paddles: vec![
-                Paddle::default_right_paddle(),
-                Paddle::default_right_paddle(),
-            ],
+            arena,
+            left_paddles: spread_paddles(
+                vec![
+                    Paddle::default_left_paddle(&arena),
+                    Paddle::default_left_paddle(&arena),
+                ],
+                &arena,
+                false,
+            ),
+            right_paddles: spread_paddles(
+                vec![
+                    Paddle::default_right_paddle(&arena),
+                    Paddle::default_right_paddle(&arena),
+                ],
+                &arena,
+                false,
+            ),
             balls: vec![
-                Ball::random_centered_ball(rng),
-                Ball::random_centered_ball(rng),
+                Ball::random_centered_ball(&arena, rng),
+                Ball::random_centered_ball(&arena, rng),
             ],
+            last_hits: Vec::new(),
+            ball_collisions: BallCollisionConfig::default(),
         }
     }
 
-    /// Update the scene given a sequence of operations. Returns the winner of the current game if
-    /// any.
-    pub fn update_scene(&mut self, ops: &mut Vec<Operation>) -> Option<Sides> {
-        let mut winner: Option<Sides> = None;
+    /// Construct a scene as described by a scene configuration. Returns an error if the
+    /// configuration is invalid.
+    pub fn construct_scene_from_config<R: Rng + ?Sized>(
+        config: &SceneConfig,
+        rng: &mut R,
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
+
+        let arena = config.screen;
+        let paddles = |side: Sides, paddles: &PaddleConfig| -> Vec<Paddle> {
+            let x = arena.paddle_center_x(side, paddles.width);
+            let constructed = (0..paddles.count)
+                .map(|index| {
+                    let pos = Vec2::new(x, arena.center().y);
+                    let mut paddle = Paddle::new(pos, paddles.width, paddles.height, side);
+                    paddle.set_speed(*paddles.speeds.get(index).unwrap_or(&paddles.speed));
+                    paddle.set_acceleration(paddles.acceleration);
+                    paddle
+                })
+                .collect();
+            spread_paddles(constructed, &arena, paddles.lanes)
+        };
+
+        let balls = &config.balls;
+        Ok(Self {
+            arena,
+            left_paddles: paddles(Sides::Left, &config.left_paddles),
+            right_paddles: paddles(Sides::Right, &config.right_paddles),
+            balls: (0..balls.count).map(|_| balls.serve(&arena, rng)).collect(),
+            last_hits: Vec::new(),
+            ball_collisions: config.ball_collisions,
+        })
+    }
+
+    /// Update the scene given a sequence of operations, advancing it by `TIME_STEP` seconds.
+    /// Returns one scoring event for each ball that is out of the arena after this update, in the
+    /// order of the balls. The balls that flew out stay in the scene, and it is up to the caller to
+    /// remove them or to serve them again. The paddle hits of this update can be read with
+    /// `get_last_hits` afterwards. Operations on paddles that are not in the scene are ignored.
+    pub fn update_scene(&mut self, ops: &mut Vec<Operation>) -> Vec<ScoringEvent> {
+        let arena = self.arena;
+        self.last_hits.clear();
+        for paddle in self
+            .left_paddles
+            .iter_mut()
+            .chain(self.right_paddles.iter_mut())
+        {
+            paddle.save_pos();
+        }
+        for ball in self.balls.iter_mut() {
+            ball.save_pos();
+        }
         use OperationTypes::*;
         use Sides::*;
+
+        // paddles that receive no up or down operation stay where they are
+        let mut left_moved = vec![false; self.left_paddles.len()];
+        let mut right_moved = vec![false; self.right_paddles.len()];
         while let Some(op) = ops.pop() {
             if let Stay = op.op_type {
                 continue;
             }
 
-            let paddle: &mut Paddle = match op.side {
-                Left => self.left_paddles.get_mut(op.index).unwrap(),
-                Right => self.right_paddles.get_mut(op.index).unwrap(),
+            let (paddles, moved) = match op.side {
+                Left => (&mut self.left_paddles, &mut left_moved),
+                Right => (&mut self.right_paddles, &mut right_moved),
             };
+            if op.index >= paddles.len() {
+                continue;
+            }
 
+            // the paddle stops where it touches another paddle of its side
+            let (min_y, max_y) = free_range(paddles, op.index);
+            let paddle = &mut paddles[op.index];
             match op.op_type {
                 Up => paddle.move_up(),
-                Down => paddle.move_down(),
+                Down => paddle.move_down(&arena),
                 _ => {}
             }
+            paddle.block(min_y, max_y);
+            moved[op.index] = true;
+        }
+        for (paddle, moved) in self
+            .left_paddles
+            .iter_mut()
+            .zip(left_moved)
+            .chain(self.right_paddles.iter_mut().zip(right_moved))
+        {
+            if !moved {
+                paddle.stay();
+            }
         }
 
-        for ball in self.balls.iter_mut() {
-            ball.update_pos();
+        // the balls the paddles moved onto are pushed out of their way before the balls move
+        for (ball_index, ball) in self.balls.iter_mut().enumerate() {
+            for (side, paddles) in [
+                (Sides::Left, &self.left_paddles),
+                (Sides::Right, &self.right_paddles),
+            ] {
+                for (paddle_index, paddle) in paddles.iter().enumerate() {
+                    if ball.get_pushed_by(paddle) {
+                        self.last_hits.push(PaddleHit {
+                            side,
+                            paddle_index,
+                            ball_index,
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut segments = Vec::with_capacity(self.balls.len());
+        for (ball_index, ball) in self.balls.iter_mut().enumerate() {
+            // move the ball to its first contact in this update, bounce, and go on with the rest
+            // of the update, so that a fast ball never passes through a paddle or an edge
+            let mut remaining = 1.0;
+            let mut segment = Segment {
+                start: ball.get_pos().clone(),
+                time: 0.0,
+            };
+            for _ in 0..MAX_BOUNCES_PER_UPDATE {
+                let mut first_contact: Option<(f32, Contact)> = None;
+                let mut consider = |t: f32, contact: Contact| {
+                    if first_contact
+                        .as_ref()
+                        .is_none_or(|(first_t, _)| t < *first_t)
+                    {
+                        first_contact = Some((t, contact));
+                    }
+                };
+
+                if let Some((t, edge)) = ball.sweep_edges(&arena, remaining) {
+                    consider(t, Contact::Edge(edge));
+                }
+                for (side, paddles) in [
+                    (Sides::Left, &self.left_paddles),
+                    (Sides::Right, &self.right_paddles),
+                ] {
+                    for (paddle_index, paddle) in paddles.iter().enumerate() {
+                        if let Some(hit) = ball.sweep_paddle(paddle, remaining) {
+                            consider(hit.t, Contact::Paddle(side, paddle_index, hit.normal));
+                        }
+                    }
+                }
+
+                let Some((t, contact)) = first_contact else {
+                    ball.advance(remaining);
+                    break;
+                };
+                ball.advance(remaining * t);
+                remaining *= 1.0 - t;
+
+                match contact {
+                    Contact::Edge(edge) => {
+                        ball.bounce_after_collision(Collision::WithEdge(&edge), &arena)
+                    }
+                    Contact::Paddle(side, paddle_index, normal) => {
+                        let paddle = match side {
+                            Sides::Left => &self.left_paddles[paddle_index],
+                            Sides::Right => &self.right_paddles[paddle_index],
+                        };
+                        ball.bounce_after_collision(Collision::WithPaddle(paddle, normal), &arena);
+                        self.last_hits.push(PaddleHit {
+                            side,
+                            paddle_index,
+                            ball_index,
+                        });
+                    }
+                }
+                segment = Segment {
+                    start: ball.get_pos().clone(),
+                    time: 1.0 - remaining,
+                };
+            }
+            segments.push(segment);
+        }
+
+        if self.ball_collisions.enabled {
+            self.collide_balls(&segments);
+        }
+
+        // every ball out of the arena scores a point for the other side
+        let mut events = Vec::new();
+        for (ball_index, ball) in self.balls.iter().enumerate() {
             let radius_in_f32 = ball.get_radius() as f32;
+            let side = if (ball.get_pos().x + radius_in_f32) < 0.0 {
+                Sides::Right
+            } else if (ball.get_pos().x - radius_in_f32) > arena.width_f32() {
+                Sides::Left
+            } else {
+                continue;
+            };
+            events.push(ScoringEvent { side, ball_index });
+        }
 
-            use Collision::*;
-            use Edges::*;
+        events
+    }
+
+    /// Bounces the balls that touched each other during the update off each other, given the last
+    /// straight segment of the path of each ball. The box around the segment of each ball is
+    /// computed first, so that only the balls whose boxes overlap are tested against each other.
+    /// Two balls are swept against each other from the time both of them move along their last
+    /// segment, since a ball that bounced off a paddle or an edge did not move in a straight line
+    /// before that. The earliest contacts are handled first, and a ball bounces off at most one
+    /// other ball per update, so a ball caught between two others is handled in the next update.
+    fn collide_balls(&mut self, segments: &[Segment]) {
+        let boxes: Vec<Aabb> = self
+            .balls
+            .iter()
+            .zip(segments)
+            .map(|(ball, segment)| {
+                Aabb::around_path(&segment.start, ball.get_pos(), ball.get_radius() as f32)
+            })
+            .collect();
+
+        // the contacts are sorted by the time of the update they happen at
+        let mut contacts: Vec<(f32, usize, usize, Vec2, Vec2, f32)> = overlapping_pairs(&boxes)
+            .into_iter()
+            .filter_map(|(i, j)| {
+                let time = segments[i].time.max(segments[j].time);
+                let start = segments[i].position_at(self.balls[i].get_pos(), time);
+                let other_start = segments[j].position_at(self.balls[j].get_pos(), time);
+                self.balls[i]
+                    .sweep_ball(&start, &self.balls[j], &other_start)
+                    .map(|t| (time + t * (1.0 - time), i, j, start, other_start, t))
+            })
+            .collect();
+        contacts.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-            // detect collision with top and bottom edge
-            if (ball.get_pos().y - radius_in_f32) < 0.0 {
-                ball.bounce_after_collision(WithEdge(&Top));
-            } else if (ball.get_pos().y + radius_in_f32) > SCREEN_HEIGHT as f32 {
-                ball.bounce_after_collision(WithEdge(&Bottom));
+        let mut bounced = vec![false; self.balls.len()];
+        for (_, i, j, start, other_start, t) in contacts {
+            if bounced[i] || bounced[j] {
+                continue;
+            }
+            // i < j, so the second ball is the first one of the right part
+            let (left, right) = self.balls.split_at_mut(j);
+            if left[i].bounce_off_ball(
+                &start,
+                &mut right[0],
+                &other_start,
+                t,
+                self.ball_collisions.restitution,
+            ) {
+                bounced[i] = true;
+                bounced[j] = true;
             }
+        }
+    }
+}
 
-            // detect collision with left paddles
-            for paddle in self.left_paddles.iter() {
-                if let Some(collision) = ball.collides_with(paddle) {
-                    ball.bounce_after_collision(collision);
-                }
+/// The last straight part of the path of a ball during an update, which starts where the ball
+/// last bounced off a paddle or an edge, or where it started the update.
+struct Segment {
+    /// Where the segment starts.
+    start: Vec2,
+    /// When the segment starts, as a fraction of the update.
+    time: f32,
+}
+
+impl Segment {
+    /// Where the ball is at the given time of the update, as a fraction of the update, given
+    /// where it is at the end of the update. The time is at least the start of the segment.
+    fn position_at(&self, end: &Vec2, time: f32) -> Vec2 {
+        if self.time >= 1.0 {
+            return end.clone();
+        }
+        let fraction = (time - self.time) / (1.0 - self.time);
+        &self.start + &(&(end - &self.start) * fraction)
+    }
+}
+
+/// Spreads the paddles of a side evenly over the height of the arena, from top to bottom, so that
+/// they do not overlap. With lanes, each paddle is also restricted to its own band of the arena.
+fn spread_paddles(mut paddles: Vec<Paddle>, arena: &ArenaConfig, lanes: bool) -> Vec<Paddle> {
+    let count = paddles.len();
+    for (index, paddle) in paddles.iter_mut().enumerate() {
+        paddle.set_y(arena.paddle_center_y(index, count));
+        if lanes {
+            paddle.set_lane(Some(arena.paddle_lane(index, count)));
+        }
+    }
+    paddles
+}
+
+/// The range of y positions the center of the paddle of the given index can be at without
+/// overlapping the other paddles of its side, given where they are. The paddles above it, or at
+/// the same height with a smaller index, limit it from above, and the others limit it from below.
+fn free_range(paddles: &[Paddle], index: usize) -> (f32, f32) {
+    let paddle = &paddles[index];
+    let y = paddle.get_pos().y;
+    let mut range = (f32::NEG_INFINITY, f32::INFINITY);
+    for (other_index, other) in paddles.iter().enumerate() {
+        if other_index == index {
+            continue;
+        }
+        let other_y = other.get_pos().y;
+        let distance = (paddle.get_height() + other.get_height()) as f32 / 2.0;
+        if other_y < y || (other_y == y && other_index < index) {
+            range.0 = range.0.max(other_y + distance);
+        } else {
+            range.1 = range.1.min(other_y - distance);
+        }
+    }
+    range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::paddle::{PaddleRegion, DEFAULT_PADDLE_WIDTH};
+    use super::super::timestep::UPDATES_PER_SECOND;
+    use super::*;
+    use crate::math_utils::collision::Aabb;
+
+    /// The velocity in pixels per second of an object moving by `(x, y)` pixels per update.
+    fn per_update(x: f32, y: f32) -> Vec2 {
+        &Vec2::new(x, y) * UPDATES_PER_SECOND as f32
+    }
+
+    /// A scene whose paddles cover the whole height of the arena, so that a ball can never get
+    /// past them without tunnelling.
+    fn walled_scene(ball: Ball) -> Scene {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        for side in [Sides::Left, Sides::Right] {
+            let x = arena.paddle_center_x(side, DEFAULT_PADDLE_WIDTH);
+            let paddle = Paddle::new(
+                Vec2::new(x, arena.center().y),
+                DEFAULT_PADDLE_WIDTH,
+                arena.height,
+                side,
+            );
+            match side {
+                Sides::Left => scene.add_left_paddles(vec![paddle]),
+                Sides::Right => scene.add_right_paddles(vec![paddle]),
             }
+        }
+        scene.add_balls(vec![ball]);
+        scene
+    }
+
+    #[test]
+    fn fast_ball_bounces_off_a_thin_paddle() {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![Paddle::default_left_paddle(&arena)]);
+        scene.add_balls(vec![Ball::new(arena.center(), per_update(-600.0, 0.0), 3)]);
+
+        let events = scene.update_scene(&mut Vec::new());
+
+        assert!(events.is_empty());
+        let ball = &scene.get_balls()[0];
+        assert!(ball.get_vel().x > 0.0);
+        let front = scene.get_left_paddles()[0].get_aabb().max.x;
+        assert!(ball.get_pos().x - 3.0 >= front - 1e-3);
+        assert_eq!(scene.get_last_hits().len(), 1);
+    }
 
-            // detect collision with right paddles
-            for paddle in self.right_paddles.iter() {
-                if let Some(collision) = ball.collides_with(paddle) {
-                    ball.bounce_after_collision(collision);
+    #[test]
+    fn balls_never_tunnel_at_extreme_speeds() {
+        let arena = ArenaConfig::default();
+        for speed in [10.0, 50.0, 200.0, 1000.0, 3000.0] {
+            for angle_in_degrees in [-60.0_f32, -30.0, -5.0, 0.0, 5.0, 30.0, 60.0] {
+                let angle = angle_in_degrees.to_radians();
+                let vel = per_update(-speed * angle.cos(), speed * angle.sin());
+                let mut scene = walled_scene(Ball::new(arena.center(), vel, 3));
+                let left_front = scene.get_left_paddles()[0].get_aabb().max.x;
+                let right_front = scene.get_right_paddles()[0].get_aabb().min.x;
+
+                for _ in 0..50 {
+                    let events = scene.update_scene(&mut Vec::new());
+                    assert!(events.is_empty(), "speed {speed}, angle {angle_in_degrees}");
+
+                    let pos = scene.get_balls()[0].get_pos();
+                    assert!(
+                        pos.x - 3.0 >= left_front - 1e-2 && pos.x + 3.0 <= right_front + 1e-2,
+                        "speed {speed}, angle {angle_in_degrees}: ball at {pos:?}"
+                    );
+                    assert!(
+                        pos.y >= 0.0 && pos.y <= arena.height_f32(),
+                        "speed {speed}, angle {angle_in_degrees}: ball at {pos:?}"
+                    );
                 }
             }
+        }
+    }
+
+    #[test]
+    fn fast_ball_bounces_several_times_in_one_update() {
+        let arena = ArenaConfig::default();
+        let mut scene = walled_scene(Ball::new(arena.center(), per_update(-2000.0, 0.0), 3));
+
+        scene.update_scene(&mut Vec::new());
+
+        assert!(scene.get_last_hits().len() >= 2);
+        assert!(scene
+            .get_last_hits()
+            .windows(2)
+            .all(|hits| hits[0].side != hits[1].side));
+    }
+
+    /// A scene with the default left paddle and the given ball.
+    fn left_paddle_scene(ball: Ball) -> Scene {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![Paddle::default_left_paddle(&arena)]);
+        scene.add_balls(vec![ball]);
+        scene
+    }
+
+    /// The box of the default left paddle.
+    fn left_paddle_aabb() -> Aabb {
+        Paddle::default_left_paddle(&ArenaConfig::default()).get_aabb()
+    }
 
-            // detect if the current game is over
-            // if so, set the winner variable to left or right
-            if (ball.get_pos().x + radius_in_f32) < 0.0 {
-                winner = Some(Sides::Right);
-            } else if (ball.get_pos().x - radius_in_f32) > SCREEN_WIDTH as f32 {
-                winner = Some(Sides::Left);
+    #[test]
+    fn contact_normals_are_classified_into_regions() {
+        let arena = ArenaConfig::default();
+        let left = Paddle::default_left_paddle(&arena);
+        let right = Paddle::default_right_paddle(&arena);
+        let diagonal = Vec2::new(0.6, -0.8);
+
+        for (paddle, front, back) in [(&left, 1.0, -1.0), (&right, -1.0, 1.0)] {
+            let region = |x: f32, y: f32| paddle.contact_region(&Vec2::new(x, y));
+            assert_eq!(region(front, 0.0), PaddleRegion::Front);
+            assert_eq!(region(back, 0.0), PaddleRegion::Back);
+            assert_eq!(region(0.0, -1.0), PaddleRegion::Top);
+            assert_eq!(region(0.0, 1.0), PaddleRegion::Bottom);
+            assert_eq!(paddle.contact_region(&diagonal), PaddleRegion::Corner);
+        }
+    }
+
+    #[test]
+    fn ball_bounces_back_off_the_front_face() {
+        let aabb = left_paddle_aabb();
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(aabb.max.x + 6.0, 300.0),
+            per_update(-5.0, 0.0),
+            3,
+        ));
+
+        scene.update_scene(&mut Vec::new());
+
+        let ball = &scene.get_balls()[0];
+        assert!(ball.get_vel().x > 0.0);
+        assert!(ball.get_pos().x - 3.0 >= aabb.max.x - 1e-3);
+        assert_eq!(scene.get_last_hits().len(), 1);
+    }
+
+    #[test]
+    fn ball_bounces_up_off_the_top_face() {
+        let aabb = left_paddle_aabb();
+        let x = (aabb.min.x + aabb.max.x) / 2.0;
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(x, aabb.min.y - 5.0),
+            per_update(0.0, 4.0),
+            3,
+        ));
+
+        scene.update_scene(&mut Vec::new());
+
+        let ball = &scene.get_balls()[0];
+        assert_eq!(*ball.get_vel(), per_update(0.0, -4.0));
+        assert_eq!(ball.get_pos().x, x);
+        assert!(ball.get_pos().y + 3.0 <= aabb.min.y + 1e-3);
+        assert_eq!(scene.get_last_hits().len(), 1);
+    }
+
+    #[test]
+    fn ball_bounces_down_off_the_bottom_face() {
+        let aabb = left_paddle_aabb();
+        let x = (aabb.min.x + aabb.max.x) / 2.0;
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(x, aabb.max.y + 5.0),
+            per_update(0.0, -4.0),
+            3,
+        ));
+
+        scene.update_scene(&mut Vec::new());
+
+        let ball = &scene.get_balls()[0];
+        assert_eq!(*ball.get_vel(), per_update(0.0, 4.0));
+        assert_eq!(ball.get_pos().x, x);
+        assert!(ball.get_pos().y - 3.0 >= aabb.max.y - 1e-3);
+    }
+
+    #[test]
+    fn ball_bounces_back_off_the_back_face() {
+        let aabb = left_paddle_aabb();
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(aabb.min.x - 3.5, 300.0),
+            per_update(5.0, 0.0),
+            1,
+        ));
+
+        let events = scene.update_scene(&mut Vec::new());
+
+        assert!(events.is_empty());
+        let ball = &scene.get_balls()[0];
+        assert_eq!(*ball.get_vel(), per_update(-5.0, 0.0));
+        assert!(ball.get_pos().x + 1.0 <= aabb.min.x + 1e-3);
+    }
+
+    #[test]
+    fn ball_reflects_diagonally_off_a_corner() {
+        // the ball moves straight down onto the top front corner, touching it at 45 degrees, so
+        // the normal is diagonal and the ball goes off to the right
+        let aabb = left_paddle_aabb();
+        let offset = 3.0 * std::f32::consts::FRAC_1_SQRT_2;
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(aabb.max.x + offset, aabb.min.y - offset - 2.0),
+            per_update(0.0, 4.0),
+            3,
+        ));
+
+        scene.update_scene(&mut Vec::new());
+
+        let ball = &scene.get_balls()[0];
+        assert!((ball.get_vel().x - per_update(4.0, 0.0).x).abs() < 1e-2);
+        assert!(ball.get_vel().y.abs() < 1e-2);
+        let corner = Vec2::new(aabb.max.x, aabb.min.y);
+        assert!((ball.get_pos() - &corner).magnitude() >= 3.0 - 1e-3);
+        assert_eq!(scene.get_last_hits().len(), 1);
+    }
+
+    #[test]
+    fn overlapping_ball_is_pushed_out_along_the_normal_only() {
+        // the ball already sinks into the top face, so it is pushed up without moving sideways
+        let arena = ArenaConfig::default();
+        let paddle = Paddle::default_left_paddle(&arena);
+        let aabb = paddle.get_aabb();
+        let mut ball = Ball::new(
+            Vec2::new(aabb.max.x - 1.0, aabb.min.y - 1.0),
+            Vec2::new(-1.0, 2.0),
+            3,
+        );
+
+        let collision = ball
+            .collides_with(&paddle)
+            .expect("the ball should overlap the paddle");
+        ball.bounce_after_collision(collision, &arena);
+
+        assert_eq!(
+            *ball.get_pos(),
+            Vec2::new(aabb.max.x - 1.0, aabb.min.y - 3.0)
+        );
+        assert_eq!(*ball.get_vel(), Vec2::new(-1.0, -2.0));
+    }
+
+    #[test]
+    fn paddle_moving_onto_a_resting_ball_pushes_it() {
+        // the paddle moves down by 10 pixels per update onto a ball resting 5 pixels below it
+        let arena = ArenaConfig::default();
+        let mut paddle = Paddle::default_left_paddle(&arena);
+        paddle.set_speed(per_update(0.0, 10.0).y);
+        let aabb = paddle.get_aabb();
+        let x = (aabb.min.x + aabb.max.x) / 2.0;
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![paddle]);
+        scene.add_balls(vec![Ball::new(
+            Vec2::new(x, aabb.max.y + 3.0 + 5.0),
+            Vec2::new(0.0, 0.0),
+            3,
+        )]);
+
+        for update in 0..10 {
+            scene.update_scene(&mut vec![Operation::new(
+                OperationTypes::Down,
+                Sides::Left,
+                0,
+            )]);
+
+            let ball = &scene.get_balls()[0];
+            let paddle = &scene.get_left_paddles()[0];
+            assert!(ball.get_pos().y - 3.0 >= paddle.get_aabb().max.y - 1e-3);
+            assert_eq!(ball.get_pos().x, x);
+            assert!(ball.get_vel().y >= paddle.get_vel() - 1e-3);
+            if update == 0 {
+                assert_eq!(scene.get_last_hits().len(), 1);
             }
         }
+    }
+
+    /// A scene without paddles with two balls of radius 3 moving towards each other along the
+    /// middle of the arena, 4 pixels apart.
+    fn head_on_scene(ball_collisions: BallCollisionConfig) -> Scene {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.set_ball_collisions(ball_collisions);
+        let center = arena.center();
+        scene.add_balls(vec![
+            Ball::new(Vec2::new(center.x - 5.0, center.y), per_update(4.0, 0.0), 3),
+            Ball::new(
+                Vec2::new(center.x + 5.0, center.y),
+                per_update(-2.0, 0.0),
+                3,
+            ),
+        ]);
+        scene
+    }
+
+    #[test]
+    fn equal_balls_exchange_their_velocities_in_an_elastic_collision() {
+        let mut scene = head_on_scene(BallCollisionConfig::default());
+
+        scene.update_scene(&mut Vec::new());
+
+        let balls = scene.get_balls();
+        assert_eq!(*balls[0].get_vel(), per_update(-2.0, 0.0));
+        assert_eq!(*balls[1].get_vel(), per_update(4.0, 0.0));
+        let distance = (balls[1].get_pos() - balls[0].get_pos()).magnitude();
+        assert!(distance >= 6.0 - 1e-3);
+    }
+
+    #[test]
+    fn balls_collide_along_their_path_after_a_paddle_bounce() {
+        // the first ball bounces off the paddle after a twelfth of the update, and only then
+        // meets the second ball, so they touch where the bent path of the first ball says
+        let aabb = left_paddle_aabb();
+        let y = (aabb.min.y + aabb.max.y) / 2.0;
+        let mut scene = left_paddle_scene(Ball::new(
+            Vec2::new(aabb.max.x + 4.0, y),
+            per_update(-12.0, 0.0),
+            3,
+        ));
+        scene.add_balls(vec![Ball::new(
+            Vec2::new(aabb.max.x + 20.0, y),
+            per_update(-6.0, 0.0),
+            3,
+        )]);
+
+        scene.update_scene(&mut Vec::new());
+
+        let balls = scene.get_balls();
+        let contact = aabb.max.x + 3.0 + 12.0 * 10.5 / 18.0;
+        assert!((balls[0].get_pos().x - contact).abs() < 1e-2);
+        assert!((balls[1].get_pos().x - contact - 6.0).abs() < 1e-2);
+        assert!((balls[0].get_vel().x - per_update(-6.0, 0.0).x).abs() < 1e-2);
+        assert!((balls[1].get_vel().x - per_update(12.0, 0.0).x).abs() < 1e-2);
+    }
+
+    #[test]
+    fn balls_stick_together_without_restitution() {
+        let mut scene = head_on_scene(BallCollisionConfig {
+            enabled: true,
+            restitution: 0.0,
+        });
+
+        scene.update_scene(&mut Vec::new());
+
+        let balls = scene.get_balls();
+        assert_eq!(*balls[0].get_vel(), per_update(1.0, 0.0));
+        assert_eq!(*balls[1].get_vel(), per_update(1.0, 0.0));
+    }
+
+    #[test]
+    fn balls_pass_through_each_other_when_collisions_are_disabled() {
+        let mut scene = head_on_scene(BallCollisionConfig {
+            enabled: false,
+            ..BallCollisionConfig::default()
+        });
+
+        scene.update_scene(&mut Vec::new());
+
+        let balls = scene.get_balls();
+        assert_eq!(*balls[0].get_vel(), per_update(4.0, 0.0));
+        assert_eq!(*balls[1].get_vel(), per_update(-2.0, 0.0));
+    }
+
+    #[test]
+    fn every_ball_out_in_the_same_update_scores() {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.set_ball_collisions(BallCollisionConfig {
+            enabled: false,
+            ..BallCollisionConfig::default()
+        });
+        scene.add_balls(vec![
+            Ball::new(Vec2::new(2.0, 100.0), per_update(-8.0, 0.0), 3),
+            Ball::new(arena.center(), per_update(1.0, 0.0), 3),
+            Ball::new(Vec2::new(2.0, 200.0), per_update(-8.0, 0.0), 3),
+            Ball::new(
+                Vec2::new(arena.width_f32() - 2.0, 300.0),
+                per_update(8.0, 0.0),
+                3,
+            ),
+        ]);
+
+        let events = scene.update_scene(&mut Vec::new());
+
+        assert_eq!(
+            events,
+            vec![
+                ScoringEvent {
+                    side: Sides::Right,
+                    ball_index: 0,
+                },
+                ScoringEvent {
+                    side: Sides::Right,
+                    ball_index: 2,
+                },
+                ScoringEvent {
+                    side: Sides::Left,
+                    ball_index: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn default_paddles_are_spread_over_the_height() {
+        let scene = Scene::construct_default_scene_with_2_balls(&mut rand::thread_rng());
+
+        for paddles in [scene.get_left_paddles(), scene.get_right_paddles()] {
+            assert_eq!(paddles[0].get_pos().y, 150.0);
+            assert_eq!(paddles[1].get_pos().y, 450.0);
+            assert!(!paddles[0].get_aabb().overlaps(&paddles[1].get_aabb()));
+        }
+    }
+
+    #[test]
+    fn paddles_block_each_other() {
+        let config =
+            SceneConfig::from_toml_str("[left_paddles]\ncount = 2\nheight = 100\nspeed = 2000.0\n")
+                .unwrap();
+        let mut scene =
+            Scene::construct_scene_from_config(&config, &mut rand::thread_rng()).unwrap();
+
+        // the upper paddle moves down into the lower one, which stays where it is
+        for _ in 0..10 {
+            scene.update_scene(&mut vec![Operation::new(
+                OperationTypes::Down,
+                Sides::Left,
+                0,
+            )]);
+        }
+
+        let paddles = scene.get_left_paddles();
+        assert_eq!(paddles[1].get_pos().y, 450.0);
+        assert_eq!(paddles[0].get_pos().y, 350.0);
+        assert_eq!(paddles[0].get_vel(), 0.0);
+    }
+
+    #[test]
+    fn operations_on_missing_paddles_are_ignored() {
+        let config = SceneConfig::from_toml_str("[left_paddles]\ncount = 1\n").unwrap();
+        let mut scene =
+            Scene::construct_scene_from_config(&config, &mut rand::thread_rng()).unwrap();
+
+        scene.update_scene(&mut vec![
+            Operation::new(OperationTypes::Up, Sides::Left, 1),
+            Operation::new(OperationTypes::Down, Sides::Right, 5),
+            Operation::new(OperationTypes::Up, Sides::Left, 0),
+        ]);
+
+        assert!(scene.get_left_paddles()[0].get_vel() < 0.0);
+        assert_eq!(scene.get_right_paddles()[0].get_vel(), 0.0);
+    }
+
+    #[test]
+    fn paddles_stay_in_their_lanes() {
+        let config = SceneConfig::from_toml_str(
+            "[right_paddles]\ncount = 3\nheight = 100\nspeed = 1500.0\nlanes = true\n",
+        )
+        .unwrap();
+        let mut scene =
+            Scene::construct_scene_from_config(&config, &mut rand::thread_rng()).unwrap();
+        assert_eq!(
+            scene.get_right_paddles()[1].get_lane(),
+            Some((200.0, 400.0))
+        );
+
+        for _ in 0..10 {
+            scene.update_scene(&mut vec![
+                Operation::new(OperationTypes::Up, Sides::Right, 1),
+                Operation::new(OperationTypes::Down, Sides::Right, 2),
+            ]);
+        }
+
+        let paddles = scene.get_right_paddles();
+        assert_eq!(paddles[1].get_pos().y, 250.0);
+        assert_eq!(paddles[2].get_pos().y, 550.0);
+    }
+
+    #[test]
+    fn paddles_stop_right_at_the_edges() {
+        let arena = ArenaConfig::default();
+        let mut paddle = Paddle::default_left_paddle(&arena);
+        paddle.set_speed(350.0);
+        let half_height = paddle.get_height() as f32 / 2.0;
+
+        // the last move up is cut short so that the top of the paddle touches the top edge
+        for _ in 0..100 {
+            paddle.move_up();
+            assert!(paddle.get_aabb().min.y >= 0.0);
+        }
+        assert_eq!(paddle.get_pos().y, half_height);
+        assert_eq!(paddle.get_vel(), 0.0);
+
+        for _ in 0..100 {
+            paddle.move_down(&arena);
+            assert!(paddle.get_aabb().max.y <= arena.height_f32());
+        }
+        assert_eq!(paddle.get_pos().y, arena.height_f32() - half_height);
+    }
+
+    #[test]
+    fn paddles_of_a_side_can_have_their_own_speeds() {
+        let config = SceneConfig::from_toml_str(
+            "[left_paddles]\ncount = 3\nheight = 50\nspeed = 5.0\nspeeds = [10.0, 2.0]\n",
+        )
+        .unwrap();
+        let scene = Scene::construct_scene_from_config(&config, &mut rand::thread_rng()).unwrap();
 
-        winner
+        let speeds: Vec<f32> = scene
+            .get_left_paddles()
+            .iter()
+            .map(|paddle| paddle.get_speed())
+            .collect();
+        assert_eq!(speeds, vec![10.0, 2.0, 5.0]);
+        assert!(
+            SceneConfig::from_toml_str("[left_paddles]\ncount = 1\nspeeds = [10.0, 2.0]\n")
+                .is_err()
+        );
     }
 }
@@ -0,0 +1,580 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use rand::Rng;
+
+use super::arena::ArenaConfig;
+use super::ball::{
+    Ball, DEFAULT_BALL_SPEED, DEFAULT_MAX_BALL_SPEED, DEFAULT_MAX_DEFLECTION, DEFAULT_RADIUS,
+};
+use super::paddle::{DEFAULT_PADDLE_HEIGHT, DEFAULT_PADDLE_SPEED, DEFAULT_PADDLE_WIDTH};
+use crate::math_utils::vec2::DEFAULT_ANGLE_RANGE;
+
+/// The minimal width and height of the arena in pixels.
+pub const MIN_ARENA_SIZE: u32 = 100;
+
+/// An error in a scene configuration. If the error is about a single field, `field` is the path
+/// of that field, such as `balls.radius`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// The path of the bad field, if the error is about a single field.
+    pub field: Option<String>,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl ConfigError {
+    /// Constructs an error about the given field.
+    fn invalid(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: Some(field.to_string()),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "Error: invalid `{field}`: {}", self.message),
+            None => write!(f, "Error: {}", self.message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// The paddles on one side of the scene.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PaddleConfig {
+    /// The number of paddles.
+    pub count: usize,
+    /// The width of each paddle in pixels.
+    pub width: u32,
+    /// The height of each paddle in pixels.
+    pub height: u32,
+    /// The speed each paddle moves at while it receives up or down operations, in pixels per
+    /// second. If the paddles accelerate, this is their maximal speed instead.
+    pub speed: f32,
+    /// The speed of each paddle, from top to bottom, for paddles that should not move at `speed`.
+    /// The paddles after the last entry move at `speed`.
+    pub speeds: Vec<f32>,
+    /// How much the speed of each paddle grows while it receives up or down operations, in pixels
+    /// per second per second. If there is none, the paddles move at full speed right away.
+    pub acceleration: Option<f32>,
+    /// Whether each paddle is restricted to its own lane. The height of the screen is split into
+    /// one lane per paddle, from top to bottom, and each paddle stays in its lane. Otherwise, the
+    /// paddles can move over the whole height but block each other.
+    pub lanes: bool,
+}
+
+impl Default for PaddleConfig {
+    /// The paddles of one side of a default game.
+    fn default() -> Self {
+        Self {
+            count: 2,
+            width: DEFAULT_PADDLE_WIDTH,
+            height: DEFAULT_PADDLE_HEIGHT,
+            speed: DEFAULT_PADDLE_SPEED,
+            speeds: Vec::new(),
+            acceleration: None,
+            lanes: false,
+        }
+    }
+}
+
+/// The balls of the scene.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct BallConfig {
+    /// The number of balls.
+    pub count: usize,
+    /// The radius of each ball in pixels.
+    pub radius: u32,
+    /// The speed each ball is launched with, in pixels per second.
+    pub speed: f32,
+    /// The range of launching angles in degrees. A ball is launched towards the left or the right
+    /// at an angle between `-launch_angle_range` and `launch_angle_range` from the horizontal.
+    pub launch_angle_range: f32,
+    /// The maximal angle between the horizontal and the direction of a ball bouncing off a paddle,
+    /// in degrees. It is reached when the ball hits the very end of the paddle, and a ball hitting
+    /// the center of a paddle leaves horizontally.
+    pub max_deflection_angle: f32,
+    /// The factor the speed of a ball is multiplied by each time it bounces off a paddle. With 1,
+    /// the speed stays constant over a rally.
+    pub speed_up_factor: f32,
+    /// The speed a ball cannot exceed by speeding up, in pixels per second.
+    pub max_speed: f32,
+    /// The fraction of the vertical velocity of a paddle that is added to the vertical velocity of
+    /// a ball bouncing off it. With 0, the motion of the paddle does not matter.
+    pub paddle_velocity_transfer: f32,
+}
+
+impl Default for BallConfig {
+    /// The balls of a default game.
+    fn default() -> Self {
+        Self {
+            count: 2,
+            radius: DEFAULT_RADIUS,
+            speed: DEFAULT_BALL_SPEED,
+            launch_angle_range: DEFAULT_ANGLE_RANGE.to_degrees(),
+            max_deflection_angle: DEFAULT_MAX_DEFLECTION.to_degrees(),
+            speed_up_factor: 1.0,
+            max_speed: DEFAULT_MAX_BALL_SPEED,
+            paddle_velocity_transfer: 0.0,
+        }
+    }
+}
+
+impl BallConfig {
+    /// Serves a ball as described by the configuration, from the center of the given arena in a
+    /// random direction.
+    pub fn serve<R: Rng + ?Sized>(&self, arena: &ArenaConfig, rng: &mut R) -> Ball {
+        let mut ball = Ball::random_centered_ball_with(
+            arena,
+            self.speed,
+            self.radius,
+            self.launch_angle_range.to_radians(),
+            rng,
+        );
+        ball.set_max_deflection(self.max_deflection_angle.to_radians());
+        ball.set_speed_up(self.speed_up_factor, self.max_speed);
+        ball.set_paddle_vel_transfer(self.paddle_velocity_transfer);
+        ball
+    }
+}
+
+/// How balls bounce off each other.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct BallCollisionConfig {
+    /// Whether balls bounce off each other. If not, they pass through one another.
+    pub enabled: bool,
+    /// The fraction of the speed at which two balls approach each other that they move apart with
+    /// after bouncing, between 0 and 1. With 1, the collisions are elastic.
+    pub restitution: f32,
+}
+
+impl Default for BallCollisionConfig {
+    /// The elastic collisions of a default game.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            restitution: 1.0,
+        }
+    }
+}
+
+/// The default score a side needs to win a match.
+pub const DEFAULT_TARGET_SCORE: u32 = 11;
+
+/// When a round ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringMode {
+    /// The round ends as soon as a ball flies out of the arena. If several balls fly out during
+    /// the same update, each of them scores.
+    FirstBall,
+    /// Each ball that flies out of the arena scores, and is removed or served again, see
+    /// `reserve_balls`. The round ends when all balls are out, or when the time limit is reached.
+    PerBall,
+}
+
+/// How rounds and matches are scored.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ScoringConfig {
+    /// The number of points a side gets for each ball that flies out of the arena on the other
+    /// side. In the `per_ball` mode, a single round can award them several times.
+    pub points_per_ball: u32,
+    /// The score a side needs to win the match.
+    pub target_score: u32,
+    /// Whether the winner of the match also needs a lead of at least two points. If so, the match
+    /// goes on after the target score is reached until one side leads by two.
+    pub win_by_two: bool,
+    /// When a round ends.
+    pub mode: ScoringMode,
+    /// Whether a ball that flew out of the arena is served again from the center instead of being
+    /// removed. Only used in the `per_ball` mode, where it needs a time limit to end the rounds.
+    pub reserve_balls: bool,
+    /// The number of seconds of game time after which a round ends, if any, even if no ball flew
+    /// out yet.
+    pub time_limit: Option<f32>,
+}
+
+impl Default for ScoringConfig {
+    /// The scoring of a default game, that is a match to 11 points won by two.
+    fn default() -> Self {
+        Self {
+            points_per_ball: 1,
+            target_score: DEFAULT_TARGET_SCORE,
+            win_by_two: true,
+            mode: ScoringMode::FirstBall,
+            reserve_balls: false,
+            time_limit: None,
+        }
+    }
+}
+
+/// A declarative description of a custom game, usually loaded from a TOML file such as
+///
+/// ```toml
+/// [screen]
+/// width = 1000
+///
+/// [left_paddles]
+/// count = 1
+/// height = 90
+/// speed = 1000.0
+///
+/// [balls]
+/// count = 3
+/// speed = 200.0
+/// launch_angle_range = 30.0
+///
+/// [scoring]
+/// time_limit = 60.0
+/// ```
+///
+/// Every field is optional and defaults to the value of the default game, so the empty file
+/// describes the default game. Speeds are in pixels per second, and the time limit is in seconds
+/// of game time.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct SceneConfig {
+    /// The dimensions of the arena.
+    pub screen: ArenaConfig,
+    /// The paddles on the left side.
+    pub left_paddles: PaddleConfig,
+    /// The paddles on the right side.
+    pub right_paddles: PaddleConfig,
+    /// The balls.
+    pub balls: BallConfig,
+    /// How the balls bounce off each other.
+    pub ball_collisions: BallCollisionConfig,
+    /// How rounds are scored.
+    pub scoring: ScoringConfig,
+}
+
+impl SceneConfig {
+    /// Parses a scene configuration from TOML and validates it.
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(text).map_err(|e| ConfigError {
+            field: e.span().and_then(|span| field_at(text, span.start)),
+            message: e.to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads a scene configuration from a TOML file and validates it.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError {
+            field: None,
+            message: format!("failed to read {}: {e}", path.display()),
+        })?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Checks that the configuration describes a playable scene. Returns an error pointing at the
+    /// first bad field otherwise.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.screen.width < MIN_ARENA_SIZE {
+            return Err(ConfigError::invalid(
+                "screen.width",
+                format!("must be at least {MIN_ARENA_SIZE}"),
+            ));
+        }
+        if self.screen.height < MIN_ARENA_SIZE {
+            return Err(ConfigError::invalid(
+                "screen.height",
+                format!("must be at least {MIN_ARENA_SIZE}"),
+            ));
+        }
+        if self.screen.paddle_margin > self.screen.width / 4 {
+            return Err(ConfigError::invalid(
+                "screen.paddle_margin",
+                format!(
+                    "must be at most a quarter of the screen width, that is {}",
+                    self.screen.width / 4
+                ),
+            ));
+        }
+
+        for (name, paddles) in [
+            ("left_paddles", &self.left_paddles),
+            ("right_paddles", &self.right_paddles),
+        ] {
+            if paddles.count == 0 {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.count"),
+                    "there must be at least one paddle on each side",
+                ));
+            }
+            if paddles.width == 0 || paddles.width > self.screen.width / 4 {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.width"),
+                    format!(
+                        "must be between 1 and a quarter of the screen width, that is {}",
+                        self.screen.width / 4
+                    ),
+                ));
+            }
+            // the paddles of a side are spread over the height of the screen without overlapping
+            let max_height = u32::try_from(paddles.count)
+                .ok()
+                .and_then(|count| self.screen.height.checked_div(count))
+                .filter(|&max_height| max_height > 0)
+                .ok_or_else(|| {
+                    ConfigError::invalid(
+                        &format!("{name}.count"),
+                        format!(
+                            "there must be at most one paddle per pixel of the screen height, \
+                             that is {}",
+                            self.screen.height
+                        ),
+                    )
+                })?;
+            if paddles.height == 0 || paddles.height > max_height {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.height"),
+                    format!(
+                        "must be between 1 and the screen height divided by the number of \
+                         paddles, that is {max_height}"
+                    ),
+                ));
+            }
+            if !paddles.speed.is_finite() || paddles.speed < 0.0 {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.speed"),
+                    "must be a non-negative number",
+                ));
+            }
+            if paddles.speeds.len() > paddles.count {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.speeds"),
+                    format!(
+                        "must have at most one speed per paddle, that is {}",
+                        paddles.count
+                    ),
+                ));
+            }
+            if paddles
+                .speeds
+                .iter()
+                .any(|speed| !speed.is_finite() || *speed < 0.0)
+            {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.speeds"),
+                    "must be non-negative numbers",
+                ));
+            }
+            if paddles
+                .acceleration
+                .is_some_and(|acceleration| !acceleration.is_finite() || acceleration <= 0.0)
+            {
+                return Err(ConfigError::invalid(
+                    &format!("{name}.acceleration"),
+                    "must be a positive number",
+                ));
+            }
+        }
+
+        if self.balls.count == 0 {
+            return Err(ConfigError::invalid(
+                "balls.count",
+                "there must be at least one ball",
+            ));
+        }
+        if self.balls.radius == 0
+            || self
+                .balls
+                .radius
+                .checked_mul(2)
+                .is_none_or(|diameter| diameter >= self.screen.height)
+        {
+            return Err(ConfigError::invalid(
+                "balls.radius",
+                "must be positive and the ball must fit into the screen",
+            ));
+        }
+        if !self.balls.speed.is_finite() || self.balls.speed <= 0.0 {
+            return Err(ConfigError::invalid(
+                "balls.speed",
+                "must be a positive number",
+            ));
+        }
+        if !(self.balls.launch_angle_range > 0.0 && self.balls.launch_angle_range < 90.0) {
+            return Err(ConfigError::invalid(
+                "balls.launch_angle_range",
+                "must be strictly between 0 and 90 degrees",
+            ));
+        }
+        if !(self.balls.max_deflection_angle >= 0.0 && self.balls.max_deflection_angle < 90.0) {
+            return Err(ConfigError::invalid(
+                "balls.max_deflection_angle",
+                "must be at least 0 and less than 90 degrees",
+            ));
+        }
+        if !(self.balls.speed_up_factor.is_finite() && self.balls.speed_up_factor >= 1.0) {
+            return Err(ConfigError::invalid(
+                "balls.speed_up_factor",
+                "must be a number of at least 1",
+            ));
+        }
+        if !(self.balls.max_speed.is_finite() && self.balls.max_speed >= self.balls.speed) {
+            return Err(ConfigError::invalid(
+                "balls.max_speed",
+                format!(
+                    "must be a number of at least the speed of the balls, that is {}",
+                    self.balls.speed
+                ),
+            ));
+        }
+        if !(self.balls.paddle_velocity_transfer.is_finite()
+            && self.balls.paddle_velocity_transfer >= 0.0)
+        {
+            return Err(ConfigError::invalid(
+                "balls.paddle_velocity_transfer",
+                "must be a non-negative number",
+            ));
+        }
+        if !(self.ball_collisions.restitution >= 0.0 && self.ball_collisions.restitution <= 1.0) {
+            return Err(ConfigError::invalid(
+                "ball_collisions.restitution",
+                "must be between 0 and 1",
+            ));
+        }
+
+        if self.scoring.points_per_ball == 0 {
+            return Err(ConfigError::invalid(
+                "scoring.points_per_ball",
+                "must be at least 1",
+            ));
+        }
+        if self.scoring.target_score == 0 {
+            return Err(ConfigError::invalid(
+                "scoring.target_score",
+                "must be at least 1",
+            ));
+        }
+        if self
+            .scoring
+            .time_limit
+            .is_some_and(|time_limit| !time_limit.is_finite() || time_limit <= 0.0)
+        {
+            return Err(ConfigError::invalid(
+                "scoring.time_limit",
+                "must be a positive number of seconds",
+            ));
+        }
+        if self.scoring.mode == ScoringMode::PerBall
+            && self.scoring.reserve_balls
+            && self.scoring.time_limit.is_none()
+        {
+            return Err(ConfigError::invalid(
+                "scoring.time_limit",
+                "is needed when balls are served again, otherwise rounds never end",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The path of the field defined on the line of a TOML text containing the given byte offset,
+/// such as `balls.radius` for an offset in `radius = -3` below a `[balls]` header. Only plain
+/// `key = value` lines are recognised, and there is no path for anything else.
+fn field_at(text: &str, offset: usize) -> Option<String> {
+    let line_start = text.get(..offset)?.rfind('\n').map_or(0, |i| i + 1);
+    let line = text[line_start..].lines().next()?;
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() || key.starts_with('[') {
+        return None;
+    }
+
+    let table = text[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(|header| header.trim_matches(|c| c == '[' || c == ']').trim());
+    Some(match table {
+        Some(table) => format!("{table}.{key}"),
+        None => key.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The path of the field the given TOML text is rejected for.
+    fn bad_field(text: &str) -> Option<String> {
+        SceneConfig::from_toml_str(text)
+            .expect_err("the configuration should be invalid")
+            .field
+    }
+
+    #[test]
+    fn bad_ball_radius_is_named() {
+        for radius in ["-3", "0", "300", "3000000000"] {
+            assert_eq!(
+                bad_field(&format!("[balls]\nradius = {radius}\n")).as_deref(),
+                Some("balls.radius"),
+                "radius {radius}"
+            );
+        }
+    }
+
+    #[test]
+    fn paddle_taller_than_the_arena_is_named() {
+        assert_eq!(
+            bad_field("[screen]\nheight = 400\n\n[right_paddles]\ncount = 1\nheight = 401\n")
+                .as_deref(),
+            Some("right_paddles.height")
+        );
+        // two paddles of a side have to fit above each other
+        assert_eq!(
+            bad_field("[left_paddles]\ncount = 2\nheight = 301\n").as_deref(),
+            Some("left_paddles.height")
+        );
+    }
+
+    #[test]
+    fn too_many_paddles_are_named() {
+        for count in ["601", "4294967296", "18446744073709551615"] {
+            assert_eq!(
+                bad_field(&format!("[left_paddles]\ncount = {count}\nheight = 1\n")).as_deref(),
+                Some("left_paddles.count"),
+                "count {count}"
+            );
+        }
+    }
+
+    #[test]
+    fn paddle_outside_the_arena_is_named() {
+        assert_eq!(
+            bad_field("[left_paddles]\nwidth = 201\n").as_deref(),
+            Some("left_paddles.width")
+        );
+        assert_eq!(
+            bad_field("[screen]\nwidth = 400\npaddle_margin = 101\n").as_deref(),
+            Some("screen.paddle_margin")
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_named() {
+        assert_eq!(
+            bad_field("[balls]\ncount = 2\nsize = 3\n").as_deref(),
+            Some("balls.size")
+        );
+    }
+}
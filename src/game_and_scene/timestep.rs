@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+/// The number of updates of the simulation per second of game time. The simulation always
+/// advances by the same amount of time per update, whatever the frame rate of the window is.
+pub const UPDATES_PER_SECOND: u32 = 50;
+
+/// The amount of game time a single update of the simulation advances by, in seconds. Speeds are
+/// given in pixels per second, so an object moves by its speed times this step per update.
+pub const TIME_STEP: f32 = 1.0 / UPDATES_PER_SECOND as f32;
+
+/// Same as `TIME_STEP`, as a duration of real time.
+pub const TIME_STEP_DURATION: Duration =
+    Duration::from_nanos(1_000_000_000 / UPDATES_PER_SECOND as u64);
+
+/// The maximal number of updates run for a single rendered frame. If rendering falls behind, for
+/// example because the window was dragged, the game slows down instead of running a burst of
+/// updates that would make it fall even further behind.
+pub const MAX_UPDATES_PER_FRAME: u32 = 5;
+
+/// An accumulator decoupling the fixed time step of the simulation from the rate at which frames
+/// are rendered. The real time elapsed between two frames is added to the accumulator, and one
+/// update is run for each full time step in it. What is left over tells how far the rendered
+/// frame is between the last two updates, so that positions can be interpolated and the motion
+/// looks smooth at any frame rate.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    /// The real time each update stands for.
+    step: Duration,
+    /// The real time that has elapsed but was not simulated yet.
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    /// Constructs an empty accumulator for updates of the given duration.
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Adds the real time elapsed since the last frame, and returns the number of updates to run
+    /// for this frame. At most `MAX_UPDATES_PER_FRAME` updates are run, and the time that would
+    /// need more updates is dropped.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+        let mut updates = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            updates += 1;
+            if updates == MAX_UPDATES_PER_FRAME {
+                self.accumulator = self.accumulator.min(self.step);
+                break;
+            }
+        }
+        updates
+    }
+
+    /// How far the current frame is between the last update and the next one, between 0 and 1.
+    /// Positions are rendered at this fraction of the way from their state before the last update
+    /// to their state after it.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f32() / self.step.as_secs_f32()).min(1.0)
+    }
+
+    /// Drops the real time that was not simulated yet, for example when a new round starts.
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+    }
+}
+
+impl Default for FixedTimestep {
+    /// Generate an accumulator for the time step of the simulation.
+    fn default() -> Self {
+        Self::new(TIME_STEP_DURATION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_rates_run_the_same_number_of_updates() {
+        // one second of real time is 50 updates, whether it is split into 30, 60 or 144 frames
+        for fps in [30, 60, 144] {
+            let mut timestep = FixedTimestep::default();
+            let frame = Duration::from_secs(1) / fps;
+            let updates: u32 = (0..fps).map(|_| timestep.advance(frame)).sum();
+            assert!(
+                (UPDATES_PER_SECOND - 1..=UPDATES_PER_SECOND).contains(&updates),
+                "{fps} fps: {updates} updates"
+            );
+            assert!((0.0..=1.0).contains(&timestep.alpha()));
+        }
+    }
+
+    #[test]
+    fn leftover_time_is_the_interpolation_factor() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(20));
+
+        assert_eq!(timestep.advance(Duration::from_millis(50)), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+
+        timestep.reset();
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn long_frames_run_a_limited_number_of_updates() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(20));
+
+        assert_eq!(
+            timestep.advance(Duration::from_secs(1)),
+            MAX_UPDATES_PER_FRAME
+        );
+        assert_eq!(timestep.advance(Duration::ZERO), 1);
+        assert_eq!(timestep.advance(Duration::ZERO), 0);
+    }
+}
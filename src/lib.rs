@@ -0,0 +1,38 @@
+//! The game logic of pong. The simulation itself does not depend on SDL2, only rendering and
+//! keyboard input do, and they are behind the `sdl` feature.
+//!
+//! The most commonly used types are re-exported at the root of the crate:
+//! - `Game`, `Scene`, `Paddle` and `Ball` for the simulation itself;
+//! - `Operation`, `OperationTypes` and `Sides` for controlling the paddles;
+//! - `PongEnv`, `VecPongEnv`, `Observation` and `ObservationLayout` for reinforcement learning;
+//! - `PixelRenderer` for rendering frames without a window.
+
+/// Agents controlling the paddles, and running games between them.
+pub mod agent;
+/// Playing the game in an SDL2 window.
+#[cfg(feature = "sdl")]
+pub mod app;
+/// The gym-style environment.
+pub mod env;
+/// The game, the scene and everything in it.
+pub mod game_and_scene;
+/// Math utilities.
+pub mod math_utils;
+/// Rendering the game, either into an SDL2 window or into an in-memory buffer.
+pub mod render;
+
+pub use crate::env::{
+    observation::{Observation, ObservationLayout},
+    pong_env::{PongEnv, StepInfo},
+    reward::RewardFn,
+    vec_env::{VecPongEnv, VecStepResult},
+};
+pub use crate::game_and_scene::{
+    ball::Ball,
+    game::Game,
+    operation::{Operation, OperationTypes},
+    paddle::Paddle,
+    scene::{Scene, Sides},
+};
+pub use crate::math_utils::vec2::Vec2;
+pub use crate::render::pixel_renderer::{PixelFormat, PixelRenderer};
@@ -1,122 +1,382 @@
 use std::error::Error;
-use std::thread::sleep;
-use std::time::Duration;
-
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
-
-mod game_and_scene;
-mod math_utils;
-mod render;
-
-use crate::{
-    game_and_scene::{
-        game::Game,
-        game_mode::GameMode,
-        operation::Operation,
-        scene::{SCREEN_HEIGHT, SCREEN_WIDTH},
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+
+use rust_pong::{
+    agent::{
+        agents::{Agent, Controller, IdleAgent, TrackingAgent},
+        headless::{run_games, run_replay, GamesSummary},
     },
-    render::game_renderer::GameRenderer,
+    app::play::{play, replay, PlayConfig, DEFAULT_NUMBER_OF_GAMES, FRAME_DURATION},
+    game_and_scene::{game::Game, game_mode::GameMode, replay::Replay, scene_config::SceneConfig},
 };
 
-/// The default background color.
-pub const DEFAULT_BACKGROUND_COLOR: Color = Color::BLACK;
-
-/// The time interval between each frame.
-pub const FRAME_DURATION: Duration = Duration::from_millis(20);
-/// Whether the game runs at full speed. If true, `FRAME_DURATION` will be ignored and each frame
-/// is rendered as soon as the calculation finished.
-const FULL_SPEED: bool = false;
-
-/// Whether it is a customized game or it is a default game. Customized games are not yet supported
-/// as it involves mapping keyboard inputs to control multiple paddles.
-const GAME_MODE: GameMode = GameMode::Default;
-/// Whether the game is being played by humans. If true, it reads keyboard inputs to control the
-/// paddles. If false, all input events are ignored except for
-/// `Event::KeyDown {keycode: Some(Keycode::Escape),..}`.
-const HUMAN_PLAYING: bool = true;
-
-/// The default number of games. It is set to 10 by default, so the program will terminate after 10
-/// games.
-const DEFAULT_NUMBER_OF_GAMES: u32 = 10;
-
-/// The main function. At this point, only the default game is implemented so the main function
-/// starts a default game.
-fn main() -> Result<(), Box<dyn Error>> {
-    // These codes are copied from sdl2 docs
-    // https://docs.rs/sdl2/latest/sdl2/
-    let sdl_context = sdl2::init()?;
+/// The default maximum number of frames of a round without a window. Two AIs can keep a rally
+/// going forever, so rounds without a window are stopped after this number of frames.
+const DEFAULT_MAX_FRAMES: u64 = 10_000;
 
-    let video_subsystem = sdl_context.video()?;
+/// Pong, in rust.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// A TOML file with default values for the options below. Options given on the command line
+    /// take precedence over the file.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
 
-    let window = video_subsystem
-        .window("Rust Pong", SCREEN_WIDTH, SCREEN_HEIGHT)
-        .position_centered()
-        .build()?;
+    /// What to do. Defaults to `play`.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    let mut canvas = window.into_canvas().build()?;
-    let mut event_pump = sdl_context.event_pump()?;
-    let mut should_quit = false;
+/// The subcommands.
+#[derive(Subcommand)]
+enum Command {
+    /// Play in a window.
+    Play {
+        #[command(flatten)]
+        game: GameArgs,
+        /// The number of matches. A match is played until a side reaches the target score.
+        #[arg(long)]
+        matches: Option<u32>,
+        #[command(flatten)]
+        window: WindowArgs,
+        /// Save a replay of the session into this file.
+        #[arg(long)]
+        record: Option<PathBuf>,
+    },
+    /// Simulate rounds between AIs without a window, as fast as possible.
+    Simulate {
+        #[command(flatten)]
+        game: GameArgs,
+        #[command(flatten)]
+        rounds: RoundArgs,
+        /// Stop a round after this number of frames and count it as a draw.
+        #[arg(long)]
+        max_frames: Option<u64>,
+        /// Save a replay of the simulation into this file.
+        #[arg(long)]
+        record: Option<PathBuf>,
+    },
+    /// Play a replay back in a window.
+    Replay {
+        /// The replay file.
+        path: PathBuf,
+        #[command(flatten)]
+        window: WindowArgs,
+    },
+    /// Evaluate the sides against each other without a window, and report how often each wins.
+    Eval {
+        #[command(flatten)]
+        game: GameArgs,
+        #[command(flatten)]
+        rounds: RoundArgs,
+        /// Stop a round after this number of frames and count it as a draw.
+        #[arg(long)]
+        max_frames: Option<u64>,
+    },
+}
+
+/// Options about the games themselves.
+#[derive(Args)]
+struct GameArgs {
+    /// The seed of the game. A random seed is used if there is none.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Who controls the left paddles.
+    #[arg(long, value_enum)]
+    left: Option<ControllerArg>,
+    /// Who controls the right paddles.
+    #[arg(long, value_enum)]
+    right: Option<ControllerArg>,
+}
 
-    canvas.set_draw_color(DEFAULT_BACKGROUND_COLOR);
-    canvas.clear();
-    canvas.present();
+/// Options about the games played without a window.
+#[derive(Args)]
+struct RoundArgs {
+    /// The number of rounds. Each round is scored on its own, as won by the side that scored the
+    /// most points during it.
+    #[arg(long)]
+    rounds: Option<u32>,
+}
 
-    // let event_pump = sdl_context.event_pump()?;
+/// Options about the window.
+#[derive(Args)]
+struct WindowArgs {
+    /// The number of frames rendered per second. The game runs at the same speed whatever it is.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "uncapped")]
+    fps: Option<u32>,
+    /// Render each frame as soon as the previous one is shown, ignoring the frame rate.
+    #[arg(long)]
+    uncapped: bool,
+    /// The width of the window. The scene is scaled to fit into the window.
+    #[arg(long)]
+    width: Option<u32>,
+    /// The height of the window.
+    #[arg(long)]
+    height: Option<u32>,
+    /// Ask for a confirmation before quitting with the escape key.
+    #[arg(long)]
+    confirm_quit: bool,
+}
 
-    let mut game = Game::new();
+/// Who controls the paddles of a side, as given on the command line or in the config file.
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ControllerArg {
+    /// A human with the keyboard.
+    Human,
+    /// The built-in AI.
+    Ai,
+    /// Nobody, the paddles never move.
+    Idle,
+}
 
-    if let GameMode::Default = GAME_MODE {
-        game.start_default_game_with_2_balls();
-    } else {
-        unimplemented!("Custom games not yet implemented!")
+impl From<ControllerArg> for Controller {
+    fn from(controller: ControllerArg) -> Self {
+        match controller {
+            ControllerArg::Human => Controller::Human,
+            ControllerArg::Ai => Controller::Ai,
+            ControllerArg::Idle => Controller::Idle,
+        }
     }
+}
 
-    let mut renderer = GameRenderer::new(game, canvas);
+/// The config file. Every field is optional and has the same meaning as the command-line option
+/// with the same name, except for the `[scene]` table, which describes a custom game.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    scene: Option<SceneConfig>,
+    matches: Option<u32>,
+    rounds: Option<u32>,
+    seed: Option<u64>,
+    left: Option<ControllerArg>,
+    right: Option<ControllerArg>,
+    fps: Option<u32>,
+    uncapped: Option<bool>,
+    width: Option<u32>,
+    height: Option<u32>,
+    confirm_quit: Option<bool>,
+    max_frames: Option<u64>,
+}
 
-    let mut ops: Vec<Operation> = Vec::new();
+impl FileConfig {
+    /// Loads the config file, or returns an empty config if there is no config file.
+    fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error: failed to read {}: {e}", path.display()))?;
+        let config: Self = toml::from_str(&text)
+            .map_err(|e| format!("Error: invalid config file {}: {e}", path.display()))?;
+        // the command line checks the range of `--fps`, but the file is not parsed by clap
+        if config.fps == Some(0) {
+            return Err(format!(
+                "Error: invalid config file {}: `fps` must be at least 1",
+                path.display()
+            )
+            .into());
+        }
+        if let Some(scene) = &config.scene {
+            scene.validate().map_err(|e| {
+                let e = match e.field {
+                    Some(field) => format!("invalid `scene.{field}`: {}", e.message),
+                    None => e.message,
+                };
+                format!("Error: invalid config file {}: {e}", path.display())
+            })?;
+        }
+        Ok(config)
+    }
 
-    let mut i = 0;
-    let number_of_games = DEFAULT_NUMBER_OF_GAMES;
-    loop {
-        if should_quit || i >= number_of_games {
-            break;
+    /// The game mode described by the config file. It is a custom game if there is a `[scene]`
+    /// table, and the default game otherwise.
+    fn game_mode(&self) -> GameMode {
+        match &self.scene {
+            Some(scene) => GameMode::Custom(Box::new(scene.clone())),
+            None => GameMode::Default,
         }
+    }
+}
 
-        i += 1;
-        // renderer.reset_game();
-        renderer.start_default_game_with_2_balls();
-
-        while renderer.update_game(&mut ops).is_none() {
-            for event in event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
-                        ..
-                    } => should_quit = true,
-                    Event::KeyDown {
-                        keycode: Some(key), ..
-                    } => {
-                        if HUMAN_PLAYING {
-                            if let Some(op) = Operation::from_key_code(key) {
-                                ops.push(op);
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+/// Builds the config of a game played in a window, from the command line and the config file.
+fn play_config(
+    game: Option<&GameArgs>,
+    matches: Option<u32>,
+    window: &WindowArgs,
+    file: &FileConfig,
+) -> PlayConfig {
+    let default = PlayConfig::default();
+    let fps = window.fps.or(file.fps);
+
+    PlayConfig {
+        frame_duration: fps.map_or(FRAME_DURATION, |fps| {
+            Duration::from_secs_f64(1.0 / fps as f64)
+        }),
+        full_speed: window.uncapped || (fps.is_none() && file.uncapped.unwrap_or(false)),
+        game_mode: file.game_mode(),
+        left: game
+            .and_then(|game| game.left)
+            .or(file.left)
+            .map_or(default.left, Controller::from),
+        right: game
+            .and_then(|game| game.right)
+            .or(file.right)
+            .map_or(default.right, Controller::from),
+        number_of_matches: matches
+            .or(file.matches)
+            .unwrap_or(default.number_of_matches),
+        seed: game.and_then(|game| game.seed).or(file.seed),
+        window_width: window.width.or(file.width).unwrap_or(default.window_width),
+        window_height: window
+            .height
+            .or(file.height)
+            .unwrap_or(default.window_height),
+        confirm_quit: window.confirm_quit || file.confirm_quit.unwrap_or(default.confirm_quit),
+    }
+}
+
+/// Builds the agent of a side of a game without a window.
+fn headless_agent(controller: Controller) -> Result<Box<dyn Agent>, String> {
+    match controller {
+        Controller::Human => Err(String::from(
+            "Error: humans can only play in a window, use `play` instead.",
+        )),
+        Controller::Ai => Ok(Box::new(TrackingAgent)),
+        Controller::Idle => Ok(Box::new(IdleAgent)),
+    }
+}
+
+/// Runs rounds without a window as described by the command line and the config file. Returns the
+/// summary of the rounds and their replay.
+fn run_headless(
+    game_args: &GameArgs,
+    round_args: &RoundArgs,
+    max_frames: Option<u64>,
+    file: &FileConfig,
+    default_right: Controller,
+) -> Result<(GamesSummary, Replay), Box<dyn Error>> {
+    let left = game_args
+        .left
+        .or(file.left)
+        .map_or(Controller::Ai, Controller::from);
+    let right = game_args
+        .right
+        .or(file.right)
+        .map_or(default_right, Controller::from);
+    let number_of_rounds = round_args
+        .rounds
+        .or(file.rounds)
+        .unwrap_or(DEFAULT_NUMBER_OF_GAMES);
+    let max_frames = max_frames.or(file.max_frames).unwrap_or(DEFAULT_MAX_FRAMES);
+    let seed = game_args.seed.or(file.seed).unwrap_or_else(rand::random);
+
+    let mut game = Game::with_seed(seed);
+    let mut replay = Replay::new(seed);
+    let summary = run_games(
+        &mut game,
+        &file.game_mode(),
+        number_of_rounds,
+        Some(max_frames),
+        headless_agent(left)?.as_mut(),
+        headless_agent(right)?.as_mut(),
+        Some(&mut replay),
+    )?;
+
+    Ok((summary, replay))
+}
+
+/// Prints the summary of a number of rounds.
+fn print_summary(summary: &GamesSummary) {
+    let rounds = summary.left_wins + summary.right_wins + summary.draws;
+    println!(
+        "{rounds} rounds: left won {}, right won {}, {} draws",
+        summary.left_wins, summary.right_wins, summary.draws
+    );
+    if rounds > 0 {
+        println!(
+            "left win rate {:.3}, right win rate {:.3}, {:.1} frames per round",
+            summary.left_wins as f64 / rounds as f64,
+            summary.right_wins as f64 / rounds as f64,
+            summary.frames as f64 / rounds as f64
+        );
+    }
+}
+
+/// The main function. It reads the command line and runs the given subcommand. Without a
+/// subcommand, it opens a window and starts a default game played by humans.
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let file = FileConfig::load(cli.config.as_deref())?;
+
+    match cli.command {
+        None => {
+            let window = WindowArgs {
+                fps: None,
+                uncapped: false,
+                width: None,
+                height: None,
+                confirm_quit: false,
+            };
+            play(&play_config(None, None, &window, &file))?;
+        }
+        Some(Command::Play {
+            game,
+            matches,
+            window,
+            record,
+        }) => {
+            let recorded = play(&play_config(Some(&game), matches, &window, &file))?;
+            if let Some(path) = record {
+                recorded.save(&path)?;
             }
-            // ops.push(Operation::new(OperationTypes::Up, Sides::Right, 0));
-            // ops.push(Operation::new(OperationTypes::Down, Sides::Left, 1));
-            renderer.render_to_canvas()?;
-            if !FULL_SPEED {
-                sleep(FRAME_DURATION);
+        }
+        Some(Command::Simulate {
+            game,
+            rounds,
+            max_frames,
+            record,
+        }) => {
+            let start = Instant::now();
+            let (summary, recorded) =
+                run_headless(&game, &rounds, max_frames, &file, Controller::Ai)?;
+            let elapsed = start.elapsed().as_secs_f64();
+
+            print_summary(&summary);
+            println!(
+                "{} frames in {elapsed:.2}s ({:.0} frames/s)",
+                summary.frames,
+                summary.frames as f64 / elapsed
+            );
+            if let Some(path) = record {
+                recorded.save(&path)?;
             }
         }
+        Some(Command::Replay { path, window }) => {
+            let recorded = Replay::load(&path)?;
+            let summary = run_replay(
+                &mut Game::with_seed(recorded.get_seed()),
+                &file.game_mode(),
+                &recorded,
+            )?;
+            print_summary(&summary);
+            replay(&play_config(None, None, &window, &file), &recorded)?;
+        }
+        Some(Command::Eval {
+            game,
+            rounds,
+            max_frames,
+        }) => {
+            let (summary, _) = run_headless(&game, &rounds, max_frames, &file, Controller::Idle)?;
+            print_summary(&summary);
+        }
     }
 
-    println!("Hello, world!");
     Ok(())
 }
@@ -0,0 +1,392 @@
+use super::vec2::Vec2;
+
+/// An axis-aligned bounding box, given by its top left and bottom right corners.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aabb {
+    /// The corner with the smallest coordinates, that is the top left corner on the screen.
+    pub min: Vec2,
+    /// The corner with the largest coordinates, that is the bottom right corner on the screen.
+    pub max: Vec2,
+}
+
+impl Aabb {
+    /// Constructs the box given its center and its size.
+    pub fn from_center(center: &Vec2, width: f32, height: f32) -> Self {
+        Self {
+            min: Vec2::new(center.x - width / 2.0, center.y - height / 2.0),
+            max: Vec2::new(center.x + width / 2.0, center.y + height / 2.0),
+        }
+    }
+
+    /// The point of the box closest to the given point. It is the point itself if it is inside
+    /// the box.
+    pub fn closest_point(&self, point: &Vec2) -> Vec2 {
+        Vec2::new(
+            point.x.clamp(self.min.x, self.max.x),
+            point.y.clamp(self.min.y, self.max.y),
+        )
+    }
+
+    /// The smallest box containing a circle of the given radius along its whole straight motion
+    /// from `start` to `end`.
+    pub fn around_path(start: &Vec2, end: &Vec2, radius: f32) -> Self {
+        Self {
+            min: Vec2::new(start.x.min(end.x) - radius, start.y.min(end.y) - radius),
+            max: Vec2::new(start.x.max(end.x) + radius, start.y.max(end.y) + radius),
+        }
+    }
+
+    /// Whether the two boxes overlap or touch.
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+/// The first contact of a moving circle with an obstacle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepHit {
+    /// The time of impact, as a fraction of the motion between 0 and 1.
+    pub t: f32,
+    /// The unit normal of the obstacle at the contact point, pointing towards the circle.
+    pub normal: Vec2,
+}
+
+/// Finds the first contact of a circle moving in a straight line with a box. The circle starts at
+/// `center` and moves by `motion`. Contacts where the circle moves away from the box, or along
+/// it, are ignored, so a circle that just bounced off the box is not caught again. A circle that
+/// already overlaps the box and moves into it hits it at time 0.
+///
+/// The circle touches the box when its center touches the box grown by the radius with rounded
+/// corners, so the motion of the center is traced against the four faces of the grown box, and
+/// against a circle around each corner of the box.
+pub fn sweep_circle_aabb(
+    center: &Vec2,
+    radius: f32,
+    motion: &Vec2,
+    aabb: &Aabb,
+) -> Option<SweepHit> {
+    if let Some(hit) = overlap(center, radius, motion, aabb) {
+        return Some(hit);
+    }
+
+    // trace the center against the slabs of the grown box
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec2::new(0.0, 0.0);
+    for (start, delta, min, max, axis_normal) in [
+        (
+            center.x,
+            motion.x,
+            aabb.min.x - radius,
+            aabb.max.x + radius,
+            Vec2::new(1.0, 0.0),
+        ),
+        (
+            center.y,
+            motion.y,
+            aabb.min.y - radius,
+            aabb.max.y + radius,
+            Vec2::new(0.0, 1.0),
+        ),
+    ] {
+        if delta == 0.0 {
+            if start < min || start > max {
+                return None;
+            }
+            continue;
+        }
+
+        let (t_near, t_far, near_normal) = if delta > 0.0 {
+            (
+                (min - start) / delta,
+                (max - start) / delta,
+                &axis_normal * -1.0,
+            )
+        } else {
+            ((max - start) / delta, (min - start) / delta, axis_normal)
+        };
+        if t_near > t_enter {
+            t_enter = t_near;
+            normal = near_normal;
+        }
+        t_exit = t_exit.min(t_far);
+    }
+    if t_enter > t_exit || t_enter > 1.0 || t_exit < 0.0 {
+        return None;
+    }
+    let starts_inside = t_enter < 0.0;
+    let t_enter = t_enter.max(0.0);
+
+    // if the center enters the grown box next to a corner, it only touches the rounded corner
+    let contact = center + &(motion * t_enter);
+    let outside_x = contact.x < aabb.min.x || contact.x > aabb.max.x;
+    let outside_y = contact.y < aabb.min.y || contact.y > aabb.max.y;
+    if outside_x && outside_y {
+        let corner = aabb.closest_point(&contact);
+        return sweep_point_circle(center, motion, &corner, radius);
+    }
+
+    // a center starting inside the grown box but not next to a corner means that the circle
+    // overlaps the box, which was handled above
+    if !starts_inside && motion.dot(&normal) < 0.0 {
+        Some(SweepHit { t: t_enter, normal })
+    } else {
+        None
+    }
+}
+
+/// The contact normal of a circle overlapping the box, if it does. The normal points from the
+/// closest point of the box to the center, or out of the nearest face if the center is inside the
+/// box.
+pub fn overlap_normal(center: &Vec2, radius: f32, aabb: &Aabb) -> Option<Vec2> {
+    let closest = aabb.closest_point(center);
+    let offset = center - &closest;
+    let distance = offset.magnitude();
+    if distance >= radius {
+        return None;
+    }
+
+    let normal = if distance > 0.0 {
+        &offset / distance
+    } else {
+        // the center is inside the box, so it is pushed out of the nearest face
+        [
+            (center.x - aabb.min.x, Vec2::new(-1.0, 0.0)),
+            (aabb.max.x - center.x, Vec2::new(1.0, 0.0)),
+            (center.y - aabb.min.y, Vec2::new(0.0, -1.0)),
+            (aabb.max.y - center.y, Vec2::new(0.0, 1.0)),
+        ]
+        .into_iter()
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, normal)| normal)
+        .unwrap_or(Vec2::new(0.0, 0.0))
+    };
+    Some(normal)
+}
+
+/// The contact at time 0 of a circle that already overlaps the box and moves into it, if any.
+fn overlap(center: &Vec2, radius: f32, motion: &Vec2, aabb: &Aabb) -> Option<SweepHit> {
+    let normal = overlap_normal(center, radius, aabb)?;
+    if motion.dot(&normal) < 0.0 {
+        Some(SweepHit { t: 0.0, normal })
+    } else {
+        None
+    }
+}
+
+/// Finds the first contact of two circles moving in straight lines, the first one from `center` by
+/// `motion` and the other one from `other_center` by `other_motion`. The normal points from the
+/// other circle towards the first one. Contacts where the circles move apart are ignored, and
+/// circles that already overlap and move towards each other touch at time 0.
+pub fn sweep_circle_circle(
+    center: &Vec2,
+    radius: f32,
+    motion: &Vec2,
+    other_center: &Vec2,
+    other_radius: f32,
+    other_motion: &Vec2,
+) -> Option<SweepHit> {
+    // trace the first center, moving relative to the other circle, against the other circle
+    // grown by the radius of the first one
+    let relative_motion = motion - other_motion;
+    sweep_point_circle(
+        center,
+        &relative_motion,
+        other_center,
+        radius + other_radius,
+    )
+}
+
+/// Finds the pairs of boxes that overlap, as indices into `boxes` with the smallest index first.
+///
+/// This is a sort and sweep along the x axis: the boxes are sorted by their left side, and each
+/// box is only compared with the boxes that start before it ends. Boxes far apart horizontally
+/// are never compared, so the cost stays close to linear when few boxes overlap, instead of
+/// comparing every pair.
+pub fn overlapping_pairs(boxes: &[Aabb]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..boxes.len()).collect();
+    order.sort_by(|&a, &b| boxes[a].min.x.total_cmp(&boxes[b].min.x));
+
+    let mut pairs = Vec::new();
+    for (position, &i) in order.iter().enumerate() {
+        for &j in &order[position + 1..] {
+            if boxes[j].min.x > boxes[i].max.x {
+                break;
+            }
+            if boxes[i].overlaps(&boxes[j]) {
+                pairs.push((i.min(j), i.max(j)));
+            }
+        }
+    }
+    pairs
+}
+
+/// Finds the first time a point moving by `motion` from `start` reaches the circle of the given
+/// center and radius, if it does so while moving towards the center.
+fn sweep_point_circle(start: &Vec2, motion: &Vec2, center: &Vec2, radius: f32) -> Option<SweepHit> {
+    // solve |start + t * motion - center| = radius for the smallest t
+    let offset = start - center;
+    let a = motion.dot(motion);
+    let b = offset.dot(motion);
+    let c = offset.dot(&offset) - radius * radius;
+    if a == 0.0 || b >= 0.0 {
+        return None;
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = ((-b - discriminant.sqrt()) / a).max(0.0);
+    if t > 1.0 {
+        return None;
+    }
+    let contact = start + &(motion * t);
+    let normal = &(&contact - center) / radius;
+    Some(SweepHit { t, normal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paddle_box() -> Aabb {
+        Aabb::from_center(&Vec2::new(100.0, 100.0), 8.0, 60.0)
+    }
+
+    #[test]
+    fn head_on_hit_on_the_front_face() {
+        let hit = sweep_circle_aabb(
+            &Vec2::new(150.0, 100.0),
+            3.0,
+            &Vec2::new(-100.0, 0.0),
+            &paddle_box(),
+        )
+        .expect("the ball should hit the paddle");
+        // the ball touches the paddle when its center is at x = 104 + 3
+        assert!((hit.t - 0.43).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn motion_that_stops_short_does_not_hit() {
+        let hit = sweep_circle_aabb(
+            &Vec2::new(150.0, 100.0),
+            3.0,
+            &Vec2::new(-40.0, 0.0),
+            &paddle_box(),
+        );
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn moving_away_does_not_hit() {
+        let hit = sweep_circle_aabb(
+            &Vec2::new(106.0, 100.0),
+            3.0,
+            &Vec2::new(50.0, 0.0),
+            &paddle_box(),
+        );
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn passing_by_does_not_hit() {
+        let hit = sweep_circle_aabb(
+            &Vec2::new(150.0, 50.0),
+            3.0,
+            &Vec2::new(-100.0, 0.0),
+            &paddle_box(),
+        );
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn rounded_corner_is_hit_with_a_diagonal_normal() {
+        // the ball moves diagonally into the top right corner at (104, 70)
+        let start = Vec2::new(124.0, 50.0);
+        let hit = sweep_circle_aabb(&start, 3.0, &Vec2::new(-40.0, 40.0), &paddle_box())
+            .expect("the ball should hit the corner");
+        let expected = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((hit.normal.x - expected).abs() < 1e-5);
+        assert!((hit.normal.y + expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn grown_box_corner_outside_the_rounded_corner_does_not_hit() {
+        // the center passes through the square corner of the grown box, but not within the
+        // radius of the corner of the box
+        let hit = sweep_circle_aabb(
+            &Vec2::new(100.5, 61.5),
+            3.0,
+            &Vec2::new(12.0, 12.0),
+            &paddle_box(),
+        );
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn circles_moving_towards_each_other_touch_halfway() {
+        let hit = sweep_circle_circle(
+            &Vec2::new(0.0, 0.0),
+            3.0,
+            &Vec2::new(10.0, 0.0),
+            &Vec2::new(26.0, 0.0),
+            3.0,
+            &Vec2::new(-10.0, 0.0),
+        )
+        .expect("the circles should touch");
+        // the gap of 20 between the circles closes at 20 per update
+        assert!((hit.t - 1.0).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec2::new(-1.0, 0.0));
+
+        let apart = sweep_circle_circle(
+            &Vec2::new(0.0, 0.0),
+            3.0,
+            &Vec2::new(-10.0, 0.0),
+            &Vec2::new(26.0, 0.0),
+            3.0,
+            &Vec2::new(10.0, 0.0),
+        );
+        assert_eq!(apart, None);
+    }
+
+    #[test]
+    fn broad_phase_finds_the_same_pairs_as_comparing_every_pair() {
+        let boxes: Vec<Aabb> = (0..40)
+            .map(|i| {
+                let center = Vec2::new((i * 37 % 101) as f32, (i * 53 % 67) as f32);
+                Aabb::from_center(&center, 8.0 + (i % 5) as f32, 6.0)
+            })
+            .collect();
+
+        let mut pairs = overlapping_pairs(&boxes);
+        pairs.sort();
+        let mut expected = Vec::new();
+        for i in 0..boxes.len() {
+            for j in i + 1..boxes.len() {
+                if boxes[i].overlaps(&boxes[j]) {
+                    expected.push((i, j));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn overlapping_ball_moving_in_hits_at_time_zero() {
+        let hit = sweep_circle_aabb(
+            &Vec2::new(105.0, 100.0),
+            3.0,
+            &Vec2::new(-10.0, 0.0),
+            &paddle_box(),
+        )
+        .expect("the ball should hit the paddle");
+        assert_eq!(hit.t, 0.0);
+        assert_eq!(hit.normal, Vec2::new(1.0, 0.0));
+    }
+}
@@ -1,2 +1,5 @@
 /// The Vec2 struct.
 pub mod vec2;
+
+/// Swept collision tests between moving circles and boxes, and the broad phase between many boxes.
+pub mod collision;
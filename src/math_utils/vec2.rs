@@ -1,12 +1,10 @@
 use std::f32::consts::FRAC_PI_4;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
-use rand::rngs::ThreadRng;
 use rand::Rng;
+#[cfg(feature = "sdl")]
 use sdl2::rect::Point;
 
-use crate::game_and_scene::scene::{SCREEN_HEIGHT, SCREEN_WIDTH};
-
 /// This constant determines the default launching angle of the ball. For example, if the angle is
 /// $\frac{\pi}{4}$, you can imagine that the screen is divided into 4 regions by lines $y=x$ and
 /// $y=-x$. The balls will only be launched to the left and right regions.
@@ -28,8 +26,22 @@ impl Vec2 {
         Self { x, y }
     }
 
+    /// The magnitude of the vec2.
+    pub fn magnitude(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    /// The dot product of two vec2s.
+    pub fn dot(&self, other: &Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
     /// Generate a random vec2 given its magnitude.
-    pub fn random_with_magnitude(magnitude: f32, range: Option<f32>, rng: &mut ThreadRng) -> Self {
+    pub fn random_with_magnitude<R: Rng + ?Sized>(
+        magnitude: f32,
+        range: Option<f32>,
+        rng: &mut R,
+    ) -> Self {
         let bound: f32 = match range {
             Some(range) => range,
             None => DEFAULT_ANGLE_RANGE,
@@ -45,6 +57,7 @@ impl Vec2 {
     }
 }
 
+#[cfg(feature = "sdl")]
 impl From<&Vec2> for Point {
     fn from(vec: &Vec2) -> Self {
         Self::new(vec.x as i32, vec.y as i32)
@@ -143,11 +156,9 @@ impl Mul<&Vec2> for f32 {
 }
 
 impl Default for Vec2 {
-    /// Generates a default vec2. A default vec2 is in the center of the screen.
+    /// Generates a default vec2, that is the zero vector. Positions that depend on the size of the
+    /// arena, such as its center, are given by `ArenaConfig`.
     fn default() -> Self {
-        Self {
-            x: (SCREEN_WIDTH / 2) as f32,
-            y: (SCREEN_HEIGHT / 2) as f32,
-        }
+        Self { x: 0.0, y: 0.0 }
     }
 }
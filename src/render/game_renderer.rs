@@ -1,15 +1,15 @@
-use sdl2::{
-    pixels::Color,
-    rect::{Point, Rect},
-    render::WindowCanvas,
-};
+use sdl2::{pixels::Color, rect::Rect, render::WindowCanvas};
 
-use crate::{
-    game_and_scene::{ball::Ball, game::Game, operation::Operation, paddle::Paddle, scene::Sides},
-    math_utils::vec2::Vec2,
-    DEFAULT_BACKGROUND_COLOR,
+use crate::game_and_scene::{
+    game::Game, game_mode::GameMode, operation::Operation, scene::ScoringEvent,
+    scene_config::ConfigError,
 };
 
+use super::{rects, scoreboard};
+
+/// The default background color.
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::BLACK;
+
 /// The default color of rectangles in the game. In this game, all paddles and balls are
 /// rectangles so this constant applies on all of them.
 pub const DEFAULT_RECT_COLOR: Color = Color::WHITE;
@@ -21,92 +21,142 @@ pub struct GameRenderer {
     game: Game,
     /// The canvas that the game is rendered onto.
     canvas: WindowCanvas,
-}
-
-/// Get the rectangle used to render on the canvas. Takes a reference to the paddle and returns an
-/// sdl2 rectangle.
-fn get_rect_from_paddle(paddle: &Paddle) -> Rect {
-    let pos: &Vec2 = paddle.get_pos();
-    Rect::from_center::<Point>(pos.into(), paddle.get_width(), paddle.get_height())
-}
-
-/// Basically the same as get_rect_from_paddle, but for balls instead.
-fn get_rect_from_ball(ball: &Ball) -> Rect {
-    let pos: &Vec2 = ball.get_pos();
-    let width = ball.get_radius() * 2;
-    Rect::from_center::<Point>(pos.into(), width, width)
+    /// How far the rendered frame is between the state of the game before its last update and
+    /// its current state, between 0 and 1. See `FixedTimestep::alpha`.
+    interpolation: f32,
 }
 
 impl GameRenderer {
     /// The constructor.
     pub fn new(game: Game, canvas: WindowCanvas) -> Self {
-        Self { game, canvas }
+        Self {
+            game,
+            canvas,
+            interpolation: 1.0,
+        }
+    }
+
+    /// Get the game being rendered.
+    pub fn get_game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Get how far the rendered frame is between the last two states of the game.
+    pub fn get_interpolation(&self) -> f32 {
+        self.interpolation
+    }
+
+    /// Set how far the rendered frame is between the last two states of the game, so that the
+    /// paddles and the balls move smoothly whatever the frame rate is.
+    pub fn set_interpolation(&mut self, interpolation: f32) {
+        self.interpolation = interpolation.clamp(0.0, 1.0);
     }
 
     /// Collect all left paddles in the game into a vector of rectangles for further rendering.
     pub fn get_left_paddle_rects(&self) -> Vec<Rect> {
-        self.game
-            .get_left_paddles()
-            .iter()
-            .map(get_rect_from_paddle)
+        rects::get_left_paddle_rects(&self.game, self.interpolation)
+            .into_iter()
+            .map(Rect::from)
             .collect()
     }
 
     /// Collect all right paddles in the game into a vector of rectangles for further rendering.
     pub fn get_right_paddle_rects(&self) -> Vec<Rect> {
-        self.game
-            .get_right_paddles()
-            .iter()
-            .map(get_rect_from_paddle)
+        rects::get_right_paddle_rects(&self.game, self.interpolation)
+            .into_iter()
+            .map(Rect::from)
             .collect()
     }
 
     /// Collect all balls in the game into a vector of rectangles for further rendering.
     pub fn get_ball_rects(&self) -> Vec<Rect> {
-        self.game
-            .get_balls()
-            .iter()
-            .map(get_rect_from_ball)
+        rects::get_ball_rects(&self.game, self.interpolation)
+            .into_iter()
+            .map(Rect::from)
             .collect()
     }
 
     /// Collect all balls and paddles in the game into a vector of rectangles for further rendering.
     pub fn get_all_rects(&self) -> Vec<Rect> {
-        let mut all_rects = Vec::new();
-
-        let mut left_paddles = self.get_left_paddle_rects();
-        let mut right_paddles = self.get_right_paddle_rects();
-        let mut balls = self.get_ball_rects();
-
-        all_rects.append(&mut left_paddles);
-        all_rects.append(&mut right_paddles);
-        all_rects.append(&mut balls);
+        rects::get_all_rects(&self.game, self.interpolation)
+            .into_iter()
+            .map(Rect::from)
+            .collect()
+    }
 
-        all_rects
+    /// Collect the scoreboard of the game, that are the scores, the round number and the state of
+    /// the match, into a vector of rectangles for further rendering.
+    pub fn get_scoreboard_rects(&self) -> Vec<Rect> {
+        scoreboard::get_scoreboard_rects(&self.game)
+            .into_iter()
+            .map(Rect::from)
+            .collect()
     }
 
-    /// Update the internal game according to a sequence of operations.
-    pub fn update_game(&mut self, ops: &mut Vec<Operation>) -> Option<Sides> {
+    /// Update the internal game according to a sequence of operations. Returns the scoring events
+    /// of this update.
+    pub fn update_game(&mut self, ops: &mut Vec<Operation>) -> Vec<ScoringEvent> {
         self.game.update(ops)
     }
 
-    /// Render the content of the game onto the canvas.
+    /// Render the content of the game and its scoreboard onto the canvas.
     pub fn render_to_canvas(&mut self) -> Result<(), String> {
+        self.render_to_canvas_with_overlay(&[])
+    }
+
+    /// Same as render_to_canvas, but also draws the given lines of text in the lower half of the
+    /// arena, one below the other, for example to ask the player a question.
+    pub fn render_to_canvas_with_overlay(&mut self, lines: &[&str]) -> Result<(), String> {
         self.canvas.clear();
         self.canvas.set_draw_color(DEFAULT_BACKGROUND_COLOR);
         self.canvas.clear();
         self.canvas.set_draw_color(DEFAULT_RECT_COLOR);
+        self.canvas.fill_rects(&self.get_scoreboard_rects())?;
         self.canvas.fill_rects(&self.get_all_rects())?;
+        if !lines.is_empty() {
+            let overlay: Vec<Rect> = scoreboard::get_overlay_rects(&self.game, lines)
+                .into_iter()
+                .map(Rect::from)
+                .collect();
+            self.canvas.fill_rects(&overlay)?;
+        }
         self.canvas.present();
         Ok(())
     }
 
-    /// Start a default game. The definition of a default game is given in main.rs.
+    /// Start a default game. The scene of a default game is given by
+    /// `Scene::construct_default_scene_with_2_balls`.
     pub fn start_default_game_with_2_balls(&mut self) {
         self.game.start_default_game_with_2_balls();
     }
 
-    /// Reset the game. This is unimplemented.
+    /// Start a match of the given game mode.
+    pub fn start_game(&mut self, game_mode: &GameMode) -> Result<(), ConfigError> {
+        self.game.start_game(game_mode)
+    }
+
+    /// Start the next round of the current match, or a new match of the given game mode if the
+    /// last round is not over. See `Game::start_next_round`.
+    pub fn start_next_round(&mut self, game_mode: &GameMode) -> Result<(), ConfigError> {
+        self.game.start_next_round(game_mode)
+    }
+
+    /// End the current round without scoring. See `Game::end_round`.
+    pub fn end_round(&mut self) {
+        self.game.end_round();
+    }
+
+    /// Pause the game.
+    pub fn pause_game(&mut self) {
+        self.game.pause();
+    }
+
+    /// Resume the game if it is paused.
+    pub fn resume_game(&mut self) {
+        self.game.resume();
+    }
+
+    /// Reset the current match. The scores are cleared and a new round is set up.
     pub fn reset_game(&mut self) {
         self.game.reset();
     }
@@ -1,2 +1,12 @@
 /// The GameRenderer struct.
+#[cfg(feature = "sdl")]
 pub mod game_renderer;
+
+/// The PixelRect struct and the rectangles of everything in a game.
+pub mod rects;
+
+/// The scoreboard, drawn from rectangles with a built-in bitmap font.
+pub mod scoreboard;
+
+/// The PixelRenderer struct.
+pub mod pixel_renderer;
@@ -0,0 +1,227 @@
+use crate::game_and_scene::{arena::ArenaConfig, game::Game};
+
+use super::rects::{self, PixelRect};
+
+/// The default background color, as RGB values. It matches the background of the SDL window.
+pub const DEFAULT_BACKGROUND_RGB: [u8; 3] = [0, 0, 0];
+/// The default color of rectangles, as RGB values. It matches the rectangles in the SDL window.
+pub const DEFAULT_RECT_RGB: [u8; 3] = [255, 255, 255];
+
+/// The format of the pixels in a rendered frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 3 bytes per pixel, that are red, green and blue.
+    Rgb,
+    /// 1 byte per pixel, that is the luminance.
+    Grayscale,
+}
+
+impl PixelFormat {
+    /// The number of bytes used by each pixel.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgb => 3,
+            PixelFormat::Grayscale => 1,
+        }
+    }
+
+    /// Converts an RGB color into the bytes of a pixel in this format.
+    fn encode(&self, rgb: [u8; 3]) -> Vec<u8> {
+        match self {
+            PixelFormat::Rgb => rgb.to_vec(),
+            PixelFormat::Grayscale => {
+                let [r, g, b] = rgb.map(f32::from);
+                vec![(0.299 * r + 0.587 * g + 0.114 * b).round() as u8]
+            }
+        }
+    }
+}
+
+/// A software rasteriser that draws a game into an in-memory buffer instead of a window. It draws
+/// the same paddles and balls as `GameRenderer`, but not the scoreboard, so it does not need SDL2,
+/// a window or a GPU. The frame can be rendered at a lower resolution than the screen, in which
+/// case every pixel that a rectangle touches is painted, so that small objects like balls never
+/// disappear.
+#[derive(Debug, Clone)]
+pub struct PixelRenderer {
+    /// The width of the rendered frame in pixels.
+    width: u32,
+    /// The height of the rendered frame in pixels.
+    height: u32,
+    /// The format of the pixels in the rendered frame.
+    format: PixelFormat,
+    /// The color of the background.
+    background: [u8; 3],
+    /// The color of the rectangles.
+    foreground: [u8; 3],
+}
+
+impl PixelRenderer {
+    /// The constructor. The frame is rendered at the given resolution, and the arena of the game is
+    /// scaled to fit into it.
+    pub fn new(width: u32, height: u32, format: PixelFormat) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            background: DEFAULT_BACKGROUND_RGB,
+            foreground: DEFAULT_RECT_RGB,
+        }
+    }
+
+    /// Generates a renderer that renders frames of the given arena at one pixel per pixel of the
+    /// arena.
+    pub fn full_resolution(arena: &ArenaConfig, format: PixelFormat) -> Self {
+        Self::new(arena.width, arena.height, format)
+    }
+
+    /// Generates a renderer that renders frames of the given arena at its resolution divided by
+    /// the given factor. A factor of 1 means no downsampling.
+    pub fn downsampled(arena: &ArenaConfig, factor: u32, format: PixelFormat) -> Self {
+        let factor = factor.max(1);
+        Self::new(
+            (arena.width / factor).max(1),
+            (arena.height / factor).max(1),
+            format,
+        )
+    }
+
+    /// Sets the colors of the background and the rectangles.
+    pub fn set_colors(&mut self, background: [u8; 3], foreground: [u8; 3]) {
+        self.background = background;
+        self.foreground = foreground;
+    }
+
+    /// Get the width of the rendered frame.
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the rendered frame.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the format of the pixels in the rendered frame.
+    pub fn get_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// The number of bytes of a rendered frame.
+    pub fn frame_len(&self) -> usize {
+        self.width as usize * self.height as usize * self.format.bytes_per_pixel()
+    }
+
+    /// Render the game into a new buffer. The buffer is laid out row by row from the top left
+    /// corner, and each pixel takes `format.bytes_per_pixel()` bytes.
+    pub fn render(&self, game: &Game) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.render_into(game, &mut buffer);
+        buffer
+    }
+
+    /// Same as render, but reuses the given buffer to avoid an allocation per frame.
+    pub fn render_into(&self, game: &Game, buffer: &mut Vec<u8>) {
+        let background = self.format.encode(self.background);
+        let foreground = self.format.encode(self.foreground);
+
+        buffer.clear();
+        buffer.reserve(self.frame_len());
+        for _ in 0..self.width as usize * self.height as usize {
+            buffer.extend_from_slice(&background);
+        }
+
+        // every frame follows an update, so the game is drawn as it is after the update
+        let arena = game.get_scene().get_arena();
+        for rect in rects::get_all_rects(game, 1.0) {
+            self.fill_rect(buffer, arena, &rect, &foreground);
+        }
+    }
+
+    /// Paint every pixel of the frame touched by the rectangle, which is given in the coordinates
+    /// of the arena.
+    fn fill_rect(&self, buffer: &mut [u8], arena: &ArenaConfig, rect: &PixelRect, pixel: &[u8]) {
+        let scale_x = self.width as f32 / arena.width_f32();
+        let scale_y = self.height as f32 / arena.height_f32();
+
+        let clamp_x = |x: f32| x.clamp(0.0, self.width as f32) as usize;
+        let clamp_y = |y: f32| y.clamp(0.0, self.height as f32) as usize;
+
+        let x_start = clamp_x((rect.x as f32 * scale_x).floor());
+        let x_end = clamp_x(((rect.x + rect.width as i32) as f32 * scale_x).ceil());
+        let y_start = clamp_y((rect.y as f32 * scale_y).floor());
+        let y_end = clamp_y(((rect.y + rect.height as i32) as f32 * scale_y).ceil());
+
+        let bytes_per_pixel = pixel.len();
+        let row_len = self.width as usize * bytes_per_pixel;
+        for y in y_start..y_end {
+            let row = &mut buffer[y * row_len..(y + 1) * row_len];
+            for x in x_start..x_end {
+                row[x * bytes_per_pixel..(x + 1) * bytes_per_pixel].copy_from_slice(pixel);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_and_scene::{ball::Ball, paddle::Paddle, scene::Scene};
+    use crate::math_utils::vec2::Vec2;
+
+    /// A game with the default left paddle in the middle of the left side, and a ball of radius 3
+    /// in the center of the default arena.
+    fn game_with_a_paddle_and_a_ball() -> Game {
+        let arena = ArenaConfig::default();
+        let mut scene = Scene::with_arena(arena);
+        scene.add_left_paddles(vec![Paddle::default_left_paddle(&arena)]);
+        scene.add_balls(vec![Ball::new(arena.center(), Vec2::new(0.0, 0.0), 3)]);
+        let mut game = Game::new();
+        game.set_scene(scene);
+        game
+    }
+
+    #[test]
+    fn full_resolution_frames_have_one_pixel_per_pixel_of_the_arena() {
+        let game = game_with_a_paddle_and_a_ball();
+        let arena = ArenaConfig::default();
+        let renderer = PixelRenderer::full_resolution(&arena, PixelFormat::Rgb);
+
+        let frame = renderer.render(&game);
+
+        assert_eq!(frame.len(), renderer.frame_len());
+        assert_eq!(frame.len(), 800 * 600 * 3);
+        let pixel = |x: usize, y: usize| &frame[(y * 800 + x) * 3..(y * 800 + x + 1) * 3];
+        assert_eq!(pixel(0, 0), DEFAULT_BACKGROUND_RGB);
+        assert_eq!(pixel(400, 300), DEFAULT_RECT_RGB);
+        assert_eq!(pixel(397, 297), DEFAULT_RECT_RGB);
+        assert_eq!(pixel(403, 300), DEFAULT_BACKGROUND_RGB);
+        assert_eq!(pixel(10, 300), DEFAULT_RECT_RGB);
+        assert_eq!(pixel(10, 269), DEFAULT_BACKGROUND_RGB);
+    }
+
+    #[test]
+    fn downsampled_frames_keep_every_touched_pixel() {
+        // the ball is 6 pixels wide, so it covers less than a pixel of a frame downsampled 10
+        // times, but it still paints the pixels it touches
+        let game = game_with_a_paddle_and_a_ball();
+        let arena = ArenaConfig::default();
+        let mut renderer = PixelRenderer::downsampled(&arena, 10, PixelFormat::Grayscale);
+        renderer.set_colors([0, 0, 0], [255, 0, 0]);
+
+        let frame = renderer.render(&game);
+
+        assert_eq!((renderer.get_width(), renderer.get_height()), (80, 60));
+        assert_eq!(frame.len(), 80 * 60);
+        let painted: Vec<usize> = (0..frame.len()).filter(|&i| frame[i] != 0).collect();
+        // the ball spans pixels 39 and 40 in both directions, and the paddle spans columns 0 and
+        // 1 of rows 27 to 32
+        let ball = [29 * 80 + 39, 29 * 80 + 40, 30 * 80 + 39, 30 * 80 + 40];
+        let paddle = (27..33).flat_map(|y| [y * 80, y * 80 + 1]);
+        let mut expected: Vec<usize> = paddle.chain(ball).collect();
+        expected.sort();
+        assert_eq!(painted, expected);
+        // red is drawn with its luminance
+        assert!(painted.iter().all(|&i| frame[i] == 76));
+    }
+}
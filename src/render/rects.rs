@@ -0,0 +1,100 @@
+use crate::{
+    game_and_scene::{ball::Ball, game::Game, paddle::Paddle},
+    math_utils::vec2::Vec2,
+};
+
+/// An axis-aligned rectangle in screen coordinates. Everything in the game is drawn as a filled
+/// rectangle, and this struct describes them without depending on any rendering backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    /// The x position of the left edge.
+    pub x: i32,
+    /// The y position of the top edge.
+    pub y: i32,
+    /// The width of the rectangle.
+    pub width: u32,
+    /// The height of the rectangle.
+    pub height: u32,
+}
+
+impl PixelRect {
+    /// The constructor.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Generates a rectangle given the position of its center, in the same way as
+    /// `sdl2::rect::Rect::from_center` does.
+    pub fn from_center(center: &Vec2, width: u32, height: u32) -> Self {
+        Self {
+            x: center.x as i32 - (width / 2) as i32,
+            y: center.y as i32 - (height / 2) as i32,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<PixelRect> for sdl2::rect::Rect {
+    fn from(rect: PixelRect) -> Self {
+        Self::new(rect.x, rect.y, rect.width, rect.height)
+    }
+}
+
+/// Get the rectangle used to render a paddle. The paddle is drawn at `alpha` of the way from its
+/// position before the last update to its current position, where an `alpha` of 1 draws it where
+/// it is. See `FixedTimestep::alpha`.
+pub fn get_rect_from_paddle(paddle: &Paddle, alpha: f32) -> PixelRect {
+    PixelRect::from_center(
+        &paddle.get_interpolated_pos(alpha),
+        paddle.get_width(),
+        paddle.get_height(),
+    )
+}
+
+/// Basically the same as get_rect_from_paddle, but for balls instead.
+pub fn get_rect_from_ball(ball: &Ball, alpha: f32) -> PixelRect {
+    let width = ball.get_radius() * 2;
+    PixelRect::from_center(&ball.get_interpolated_pos(alpha), width, width)
+}
+
+/// Collect all left paddles in the game into a vector of rectangles.
+pub fn get_left_paddle_rects(game: &Game, alpha: f32) -> Vec<PixelRect> {
+    game.get_left_paddles()
+        .iter()
+        .map(|paddle| get_rect_from_paddle(paddle, alpha))
+        .collect()
+}
+
+/// Collect all right paddles in the game into a vector of rectangles.
+pub fn get_right_paddle_rects(game: &Game, alpha: f32) -> Vec<PixelRect> {
+    game.get_right_paddles()
+        .iter()
+        .map(|paddle| get_rect_from_paddle(paddle, alpha))
+        .collect()
+}
+
+/// Collect all balls in the game into a vector of rectangles.
+pub fn get_ball_rects(game: &Game, alpha: f32) -> Vec<PixelRect> {
+    game.get_balls()
+        .iter()
+        .map(|ball| get_rect_from_ball(ball, alpha))
+        .collect()
+}
+
+/// Collect all balls and paddles in the game into a vector of rectangles.
+pub fn get_all_rects(game: &Game, alpha: f32) -> Vec<PixelRect> {
+    let mut all_rects = Vec::new();
+
+    all_rects.append(&mut get_left_paddle_rects(game, alpha));
+    all_rects.append(&mut get_right_paddle_rects(game, alpha));
+    all_rects.append(&mut get_ball_rects(game, alpha));
+
+    all_rects
+}
@@ -0,0 +1,197 @@
+use crate::game_and_scene::{
+    arena::ArenaConfig,
+    game::{Game, GameState},
+    scene::Sides,
+};
+
+use super::rects::PixelRect;
+
+/// The width of a glyph in cells.
+pub const GLYPH_WIDTH: u32 = 3;
+/// The height of a glyph in cells.
+pub const GLYPH_HEIGHT: u32 = 5;
+/// The number of empty cells between two glyphs.
+pub const GLYPH_SPACING: u32 = 1;
+
+/// The bitmap of a character, as one row of `GLYPH_WIDTH` bits per line from top to bottom. The
+/// most significant bit is the leftmost cell. Only digits and the letters used by the scoreboard
+/// are supported, and every other character is drawn as a space.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    let rows = match c.to_ascii_uppercase() {
+        '0' | 'O' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'Q' => [0b111, 0b101, 0b101, 0b111, 0b001],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        '?' => [0b111, 0b001, 0b010, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// The width of a line of text in pixels, when each cell of a glyph is a square of `cell_size`
+/// pixels.
+pub fn text_width(text: &str, cell_size: u32) -> u32 {
+    let len = text.chars().count() as u32;
+    if len == 0 {
+        return 0;
+    }
+    (len * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING) * cell_size
+}
+
+/// The height of a line of text in pixels, when each cell of a glyph is a square of `cell_size`
+/// pixels.
+pub fn text_height(cell_size: u32) -> u32 {
+    GLYPH_HEIGHT * cell_size
+}
+
+/// Collect the rectangles drawing a line of text whose top left corner is at `(x, y)`. Each lit
+/// cell of a glyph is a square of `cell_size` pixels, so no font is needed.
+pub fn get_text_rects(text: &str, x: i32, y: i32, cell_size: u32) -> Vec<PixelRect> {
+    let mut rects = Vec::new();
+    let advance = ((GLYPH_WIDTH + GLYPH_SPACING) * cell_size) as i32;
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+        let glyph_x = x + i as i32 * advance;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    rects.push(PixelRect::new(
+                        glyph_x + (column * cell_size) as i32,
+                        y + (row as u32 * cell_size) as i32,
+                        cell_size,
+                        cell_size,
+                    ));
+                }
+            }
+        }
+    }
+
+    rects
+}
+
+/// Same as get_text_rects, but the text is centered horizontally on `center_x`.
+pub fn get_centered_text_rects(
+    text: &str,
+    center_x: i32,
+    y: i32,
+    cell_size: u32,
+) -> Vec<PixelRect> {
+    let x = center_x - (text_width(text, cell_size) / 2) as i32;
+    get_text_rects(text, x, y, cell_size)
+}
+
+/// The size of a cell of the small glyphs of the scoreboard in the given arena. The scores are
+/// drawn at twice this size.
+pub fn cell_size(arena: &ArenaConfig) -> u32 {
+    (arena.height / 100).max(1)
+}
+
+/// The message shown in the middle of the arena for the state of the game, if any.
+fn state_message(game: &Game) -> Option<&'static str> {
+    match game.get_state() {
+        GameState::Serving => Some("SERVE"),
+        GameState::Paused => Some("PAUSED"),
+        GameState::RoundOver => Some("ROUND OVER"),
+        GameState::MatchOver if game.get_round() > 0 => Some("GAME OVER"),
+        _ => None,
+    }
+}
+
+/// Collect the rectangles of the scoreboard of the game: the scores of both sides at the top of
+/// the arena, the round number at the bottom, and the state of the match in the middle.
+pub fn get_scoreboard_rects(game: &Game) -> Vec<PixelRect> {
+    let arena = game.get_scene().get_arena();
+    let cell = cell_size(arena);
+    let score_cell = cell * 2;
+    let center_x = (arena.width / 2) as i32;
+    let margin = (cell * 3) as i32;
+    let mut rects = Vec::new();
+
+    // the left score ends left of the center, and the right score starts right of it
+    let (left, right) = game.get_scores();
+    let left = left.to_string();
+    let right = right.to_string();
+    let gap = (score_cell * 4) as i32;
+    rects.append(&mut get_text_rects(
+        &left,
+        center_x - gap - text_width(&left, score_cell) as i32,
+        margin,
+        score_cell,
+    ));
+    rects.append(&mut get_text_rects(
+        &right,
+        center_x + gap,
+        margin,
+        score_cell,
+    ));
+
+    if game.get_round() > 0 {
+        let round = format!("ROUND {}", game.get_round());
+        let y = arena.height as i32 - margin - text_height(cell) as i32;
+        rects.append(&mut get_centered_text_rects(&round, center_x, y, cell));
+    }
+
+    if let Some(message) = state_message(game) {
+        let y = (arena.height / 2) as i32 - text_height(score_cell) as i32 * 2;
+        rects.append(&mut get_centered_text_rects(
+            message, center_x, y, score_cell,
+        ));
+
+        if let (GameState::MatchOver, Some(winner)) = (game.get_state(), game.get_match_winner()) {
+            let winner = match winner {
+                Sides::Left => "LEFT WINS",
+                Sides::Right => "RIGHT WINS",
+            };
+            let y = y + text_height(score_cell) as i32 + margin;
+            rects.append(&mut get_centered_text_rects(winner, center_x, y, cell));
+        }
+    }
+
+    rects
+}
+
+/// Collect the rectangles of lines of text drawn in the lower half of the arena of the game, one
+/// below the other and centered horizontally.
+pub fn get_overlay_rects(game: &Game, lines: &[&str]) -> Vec<PixelRect> {
+    let arena = game.get_scene().get_arena();
+    let cell = cell_size(arena) * 2;
+    let center_x = (arena.width / 2) as i32;
+    let line_height = (text_height(cell) + cell * 2) as i32;
+    let mut rects = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let y = (arena.height * 5 / 8) as i32 + i as i32 * line_height;
+        rects.append(&mut get_centered_text_rects(line, center_x, y, cell));
+    }
+
+    rects
+}